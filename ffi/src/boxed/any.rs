@@ -0,0 +1,223 @@
+use std::{
+  any::type_name,
+  ffi::c_void,
+  fmt::{Debug, Formatter},
+  hash::{Hash, Hasher},
+  mem::forget,
+  slice, str,
+};
+
+use super::{RTBox, header_of};
+use crate::FFISafe;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// A type identifier that is the same in every dylib built with the same saffi
+///
+/// Unlike [`std::any::TypeId`], this is computed from the path of the type and its
+/// layout, so the host & the plugins agree on it even though they were compiled separately.
+///
+/// The id keeps a pointer to the path next to its hash, two ids whose hashes match are
+/// only equal if their whole paths are too. A hash collision thus never makes two types
+/// equal.
+pub struct FfiTypeId {
+  path: u64,
+  layout: u64,
+
+  name: *const u8,
+  name_len: usize,
+}
+
+// SAFETY: The name is a `&'static str`
+unsafe impl Send for FfiTypeId {}
+unsafe impl Sync for FfiTypeId {}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+  let mut i = 0;
+
+  while i < bytes.len() {
+    hash ^= bytes[i] as u64;
+    hash = hash.wrapping_mul(FNV_PRIME);
+
+    i += 1;
+  }
+
+  hash
+}
+
+impl FfiTypeId {
+  const fn from_parts(path: &str, size: usize, align: usize) -> Self {
+    let layout = fnv1a(FNV_OFFSET, &(size as u64).to_le_bytes());
+
+    Self {
+      path: fnv1a(FNV_OFFSET, path.as_bytes()),
+      layout: fnv1a(layout, &(align as u64).to_le_bytes()),
+      name: path.as_ptr(),
+      name_len: path.len(),
+    }
+  }
+
+  pub const fn of<T>() -> Self {
+    Self::from_parts(type_name::<T>(), size_of::<T>(), align_of::<T>())
+  }
+
+  /// The id of `[T]`, whose layout is the one of its elements
+  pub const fn of_slice<T>() -> Self {
+    Self::from_parts(type_name::<[T]>(), size_of::<T>(), align_of::<T>())
+  }
+
+  /// The path of the type, as given by [`type_name`]
+  pub fn name(&self) -> &'static str {
+    // SAFETY: It was taken from a `&'static str`
+    unsafe { str::from_utf8_unchecked(slice::from_raw_parts(self.name, self.name_len)) }
+  }
+}
+
+impl PartialEq for FfiTypeId {
+  fn eq(&self, other: &Self) -> bool {
+    // The names are only compared when the hashes match, which is rare for
+    // different types
+    self.path == other.path && self.layout == other.layout && self.name() == other.name()
+  }
+}
+
+impl Eq for FfiTypeId {}
+
+impl Hash for FfiTypeId {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.path.hash(state);
+    self.layout.hash(state);
+  }
+}
+
+impl Debug for FfiTypeId {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "FfiTypeId({:016x}{:016x}, {})",
+      self.path,
+      self.layout,
+      self.name()
+    )
+  }
+}
+
+#[repr(transparent)]
+/// An owned value of any type, that can be downcasted in any dylib
+///
+/// This is an RTBox whose type was erased, its [`FfiTypeId`] is read from the header.
+/// Just like the RTBox, this is a thin pointer to the data.
+pub struct FfiAny {
+  inner: RTBox<c_void>,
+}
+
+unsafe impl FFISafe for FfiAny {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    crate::I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl FfiAny {
+  pub fn new<T: FFISafe + 'static>(data: T) -> Option<Self> {
+    Some(Self::from(RTBox::new(data)?))
+  }
+
+  #[inline(always)]
+  pub fn type_id(&self) -> FfiTypeId {
+    unsafe { (*header_of(self.inner.ptr.as_ptr()))._vtable.type_id }
+  }
+
+  #[inline(always)]
+  pub fn is<T: FFISafe + 'static>(&self) -> bool {
+    self.type_id() == FfiTypeId::of::<T>()
+  }
+
+  /// Returns the value if its [`FfiTypeId`] is the one of `T`
+  pub fn downcast_ref<T: FFISafe + 'static>(&self) -> Option<&T> {
+    if !self.is::<T>() {
+      return None;
+    }
+
+    Some(unsafe { &*(self.inner.ptr.as_ptr() as *const T) })
+  }
+
+  pub fn downcast_mut<T: FFISafe + 'static>(&mut self) -> Option<&mut T> {
+    if !self.is::<T>() {
+      return None;
+    }
+
+    Some(unsafe { &mut *(self.inner.ptr.as_ptr() as *mut T) })
+  }
+
+  /// Turns this back into the box it was created from
+  pub fn downcast<T: FFISafe + 'static>(self) -> Result<RTBox<T>, Self> {
+    if !self.is::<T>() {
+      return Err(self);
+    }
+
+    let ptr = self.into_raw();
+
+    Ok(unsafe { RTBox::from_thin_raw(ptr).unwrap_unchecked() })
+  }
+
+  /// Gives up ownership, returning the data pointer of the box
+  ///
+  /// The value must be given back to [`FfiAny::from_raw`], possibly in another dylib,
+  /// to be freed. There is no `leak`, since the type is not known, downcast first.
+  pub fn into_raw(self) -> *mut c_void {
+    let ptr = self.inner.ptr.as_ptr();
+    forget(self);
+
+    ptr as _
+  }
+
+  #[inline(always)]
+  /// Takes ownership of a pointer returned by [`FfiAny::into_raw`] or
+  /// [`RTBox::into_thin_raw`], `None` if it is null
+  ///
+  /// # Safety
+  ///
+  /// The pointer must be the data pointer of an RTBox of any sized type built with the
+  /// same saffi version, and nobody else may own it.
+  pub unsafe fn from_raw(data: *mut c_void) -> Option<Self> {
+    Some(Self {
+      inner: unsafe { RTBox::from_thin_raw(data)? },
+    })
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`FfiAny::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the value is dropped.
+  pub fn as_raw(&self) -> *const c_void {
+    self.inner.ptr.as_ptr() as _
+  }
+
+  /// Lends the pointer of [`FfiAny::into_raw`] to `f`, e.g. to hand it to foreign code
+  /// for the duration of a call
+  ///
+  /// `f` may read and write the data through it, but must neither free it nor keep it
+  /// after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut c_void) -> R) -> R {
+    f(self.inner.ptr.as_ptr() as _)
+  }
+}
+
+impl<T: FFISafe + 'static> From<RTBox<T>> for FfiAny {
+  fn from(value: RTBox<T>) -> Self {
+    Self {
+      inner: unsafe { RTBox::from_thin_raw(value.into_thin_raw()).unwrap_unchecked() },
+    }
+  }
+}
+
+impl Debug for FfiAny {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FfiAny")
+      .field("type_id", &self.type_id())
+      .finish_non_exhaustive()
+  }
+}
@@ -0,0 +1,166 @@
+use std::{
+  ffi::c_void,
+  marker::PhantomData,
+  ptr::{self, NonNull},
+};
+
+use super::{FfiTypeId, RTBox, RTBoxVTable, RTBoxWrapper, header_of, poison, verify};
+use crate::FFISafe;
+
+/// Destroys the value of a box, `data` is the data pointer of the box and `ctx`
+/// the context given when the box was created
+///
+/// The memory of the box itself is freed by saffi afterwards.
+pub type RTBoxDeleteFn = unsafe extern "C" fn(data: *mut c_void, ctx: *mut c_void);
+
+#[repr(C)]
+/// Sits right before the [`RTBoxWrapper`] of boxes that were created with a custom deleter
+///
+/// The allocation is laid out as `[padding][RTBoxDeleter][RTBoxWrapper][T]`
+pub struct RTBoxDeleter {
+  pub delete: RTBoxDeleteFn,
+  /// May be null
+  pub ctx: *mut c_void,
+}
+
+const fn rt_deleter_offset(align: usize) -> usize {
+  (size_of::<RTBoxDeleter>() + size_of::<RTBoxWrapper>()).next_multiple_of(align)
+}
+
+#[inline(always)]
+const unsafe fn deleter_of(data: *mut u8) -> *mut RTBoxDeleter {
+  unsafe { (header_of(data) as *mut RTBoxDeleter).sub(1) }
+}
+
+const fn vtable_deleter<T: FFISafe>() -> &'static RTBoxVTable {
+  const {
+    &RTBoxVTable {
+      free: mfree_deleter::<T>,
+      // The value may only be destroyed by its deleter
+      dealloc: None,
+      clone: None,
+      type_id: FfiTypeId::of::<T>(),
+    }
+  }
+}
+
+impl<T: FFISafe> RTBox<T> {
+  /// Boxes the value, which is destroyed by `delete` instead of being dropped
+  ///
+  /// This lets foreign code hand its own resources, like a pointer to an object
+  /// of a C library, to Rust as an `RTBox`. The box is dropped as usual, which calls
+  /// `delete(data, null)` and then frees the memory of the box.
+  ///
+  /// Since the value must go through `delete`, [`RTBox::into_inner`] always fails
+  /// for these boxes.
+  ///
+  /// # Safety
+  ///
+  /// `delete` must be safe to call with a pointer to the value exactly once, and must
+  /// leave the memory of the box alone.
+  pub unsafe fn with_deleter(data: T, delete: RTBoxDeleteFn) -> Option<Self> {
+    unsafe { Self::with_deleter_ctx(data, delete, ptr::null_mut()) }
+  }
+
+  /// Same as [`RTBox::with_deleter`], `ctx` is passed as is to `delete`
+  ///
+  /// # Safety
+  ///
+  /// Same as [`RTBox::with_deleter`], `ctx` must stay valid until the box is dropped,
+  /// possibly on another thread.
+  pub unsafe fn with_deleter_ctx(data: T, delete: RTBoxDeleteFn, ctx: *mut c_void) -> Option<Self> {
+    unsafe {
+      let ptr = Self::alloc_at(
+        rt_deleter_offset(align_of::<T>()),
+        size_of::<T>(),
+        align_of::<T>(),
+        0,
+        vtable_deleter::<T>(),
+        salloc::aligned_malloc,
+      )?;
+
+      ptr::write(deleter_of(ptr.as_ptr()), RTBoxDeleter { delete, ctx });
+      ptr::write(ptr.as_ptr() as *mut T, data);
+
+      Some(Self {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+}
+
+unsafe extern "C" fn mfree_deleter<T: FFISafe>(data: *mut c_void) {
+  unsafe {
+    let RTBoxDeleter { delete, ctx } = ptr::read(deleter_of(data as _));
+    delete(data, ctx);
+    poison::<T>(data, 1);
+
+    let base_ptr = (data as *mut u8).sub(rt_deleter_offset(align_of::<T>()));
+    salloc::aligned_free(base_ptr as _);
+  }
+}
+
+/// Boxes a foreign resource, returning the data pointer of an `RTBox<*mut c_void>`
+///
+/// When the box is dropped, `delete` is called with a pointer to the stored
+/// `resource` (a `void **`) and `ctx`. Returns null if the allocation failed.
+///
+/// # Safety
+///
+/// See [`RTBox::with_deleter_ctx`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_rtbox_adopt(
+  resource: *mut c_void,
+  delete: RTBoxDeleteFn,
+  ctx: *mut c_void,
+) -> *mut c_void {
+  match unsafe { RTBox::with_deleter_ctx(resource, delete, ctx) } {
+    Some(boxed) => boxed.into_thin_raw(),
+    None => ptr::null_mut(),
+  }
+}
+
+/// Drops the box behind the data pointer, whatever its type is
+///
+/// # Safety
+///
+/// `data` must be the exclusively owned data pointer of an RTBox, or null
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_rtbox_drop(data: *mut c_void) {
+  let Some(data) = NonNull::new(data) else {
+    return;
+  };
+
+  unsafe {
+    verify(data.as_ptr() as _);
+
+    let header = header_of(data.as_ptr() as _);
+
+    ((*header)._vtable.free)(data.as_ptr());
+  }
+}
+
+/// Clones the box behind the data pointer, whatever its type is
+///
+/// Returns the data pointer of the new box, or null if the box cannot be cloned or
+/// the allocation failed. See [`RTBox::try_clone`].
+///
+/// # Safety
+///
+/// `data` must be the data pointer of a live RTBox
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_rtbox_clone(data: *const c_void) -> *mut c_void {
+  if data.is_null() {
+    return ptr::null_mut();
+  }
+
+  unsafe {
+    verify(data as _);
+
+    match (*header_of(data as _))._vtable.clone {
+      Some(clone) => clone(data),
+      None => ptr::null_mut(),
+    }
+  }
+}
@@ -1,119 +1,674 @@
 use std::{
   ffi::c_void,
-  mem::{forget, offset_of},
+  marker::{PhantomData, Unsize},
+  mem::{MaybeUninit, forget, transmute_copy},
   ops::{Deref, DerefMut},
-  ptr::{self, NonNull, addr_of_mut},
+  pin::Pin,
+  ptr::{self, NonNull, Pointee},
 };
 
+pub mod any;
+pub mod foreign;
+
+pub use any::{FfiAny, FfiTypeId};
+
 use crate::{
   FFISafe,
   I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  hardened::{self, TAG_RTBOX},
+  string::str::SharableStr,
+  vector::Vector,
 };
 
 #[repr(C)]
-pub struct RTBoxWrapper<T: FFISafe> {
-  _free: unsafe extern "C" fn(data: *mut c_void),
-  _t: T,
+/// The functions of a boxed type, filled in by the dylib that allocated the box
+///
+/// Every function takes the data pointer of the box.
+pub struct RTBoxVTable {
+  /// Drops the data and frees the allocation
+  pub free: unsafe extern "C" fn(data: *mut c_void),
+  /// Frees the allocation without dropping the data, the data must have been
+  /// moved out before
+  ///
+  /// This is `None` when the data may only be destroyed through `free`
+  pub dealloc: Option<unsafe extern "C" fn(data: *mut c_void)>,
+  /// Allocates a new box holding a clone of the data, returning its data pointer
+  /// or null if the allocation failed
+  ///
+  /// This is `None` when the data cannot be cloned
+  pub clone: Option<unsafe extern "C" fn(data: *const c_void) -> *mut c_void>,
+  /// The type of the data, which stays the same across dylibs, see [`FfiTypeId`]
+  pub type_id: FfiTypeId,
 }
 
-pub struct RTBox<T: FFISafe> {
-  ptr: NonNull<T>,
+#[repr(C)]
+/// The header that directly precedes the data of every RTBox, whatever its type is
+///
+/// The allocation is laid out as `[padding][RTBoxWrapper][T]`, so foreign code can
+/// always find the vtable right before the data pointer without knowing `T`.
+pub struct RTBoxWrapper {
+  #[cfg(feature = "hardened")]
+  _guard: hardened::Guard,
+
+  /// The pointer metadata of `T`, i.e. the length for slices & strings
+  /// and the vtable pointer for trait objects. Unused for sized types.
+  pub(crate) _meta: usize,
+  pub(crate) _vtable: &'static RTBoxVTable,
 }
 
-unsafe impl<T: FFISafe> FFISafe for RTBox<T> {
+impl RTBoxWrapper {
+  #[inline(always)]
+  pub(crate) const fn new(meta: usize, vtable: &'static RTBoxVTable) -> Self {
+    Self {
+      #[cfg(feature = "hardened")]
+      _guard: hardened::Guard::new(TAG_RTBOX),
+      _meta: meta,
+      _vtable: vtable,
+    }
+  }
+}
+
+/// The offset of the data from the start of the allocation, for data of the given alignment
+///
+/// This is the same offset as the one of `Vector<T>`, which lets `Vector<T>` turn into
+/// `RTBox<[T]>` in place.
+const fn rt_data_offset(align: usize) -> usize {
+  size_of::<RTBoxWrapper>().next_multiple_of(align)
+}
+
+const fn rt_alloc_align(align: usize) -> usize {
+  let ptr = align_of::<RTBoxWrapper>();
+
+  if align > ptr { align } else { ptr }
+}
+
+const _SAFETY: () = assert!(rt_data_offset(1) == crate::vector::data_offset::<u8>() as usize);
+const _SAFETY_2: () = assert!(rt_data_offset(8) == crate::vector::data_offset::<u64>() as usize);
+
+#[inline(always)]
+const fn meta_to_usize<T: ?Sized>(meta: <T as Pointee>::Metadata) -> usize {
+  const { assert!(size_of::<<T as Pointee>::Metadata>() <= size_of::<usize>()) };
+
+  if size_of::<<T as Pointee>::Metadata>() == 0 {
+    0
+  } else {
+    unsafe { transmute_copy(&meta) }
+  }
+}
+
+#[inline(always)]
+const unsafe fn usize_to_meta<T: ?Sized>(meta: usize) -> <T as Pointee>::Metadata {
+  unsafe { transmute_copy(&meta) }
+}
+
+#[inline(always)]
+fn vtable<T: FFISafe>() -> &'static RTBoxVTable {
+  const {
+    &RTBoxVTable {
+      free: mfree::<T>,
+      dealloc: Some(mdealloc::<T>),
+      clone: None,
+      type_id: FfiTypeId::of::<T>(),
+    }
+  }
+}
+
+#[inline(always)]
+/// Same as [`vtable`] with the clone entry filled in
+fn cloneable_vtable<T: FFISafe + Clone>() -> &'static RTBoxVTable {
+  const {
+    &RTBoxVTable {
+      free: mfree::<T>,
+      dealloc: Some(mdealloc::<T>),
+      clone: Some(mclone::<T>),
+      type_id: FfiTypeId::of::<T>(),
+    }
+  }
+}
+
+#[inline(always)]
+fn vtable_slice<T: FFISafe>() -> &'static RTBoxVTable {
+  const {
+    &RTBoxVTable {
+      free: mfree_slice::<T>,
+      dealloc: Some(mdealloc::<T>),
+      clone: None,
+      type_id: FfiTypeId::of_slice::<T>(),
+    }
+  }
+}
+
+#[inline(always)]
+/// Same as [`vtable_slice`] with the clone entry filled in
+fn cloneable_vtable_slice<T: FFISafe + Clone>() -> &'static RTBoxVTable {
+  const {
+    &RTBoxVTable {
+      free: mfree_slice::<T>,
+      dealloc: Some(mdealloc::<T>),
+      clone: Some(mclone_slice::<T>),
+      type_id: FfiTypeId::of_slice::<T>(),
+    }
+  }
+}
+
+#[inline(always)]
+pub(crate) const unsafe fn header_of(data: *mut u8) -> *mut RTBoxWrapper {
+  unsafe { data.sub(size_of::<RTBoxWrapper>()) as _ }
+}
+
+#[inline(always)]
+#[track_caller]
+/// Checks the header canary, see the `hardened` feature
+pub(crate) unsafe fn verify(data: *mut u8) {
+  unsafe { hardened::verify(header_of(data) as _, TAG_RTBOX) };
+}
+
+#[repr(transparent)]
+/// A box allocated through salloc that can be dropped from any dylib
+///
+/// `RTBox` is always a thin pointer to the data, even when `T` is unsized like
+/// `[T]`, `str` or `dyn Trait`. The metadata of those lives in [`RTBoxWrapper`].
+pub struct RTBox<T: ?Sized> {
+  ptr: NonNull<u8>,
+
+  _t: PhantomData<T>,
+}
+
+unsafe impl<T: ?Sized> FFISafe for RTBox<T> {
   fn i_am_ffisafe() -> crate::IAmFFISafe {
     I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
   }
 }
-unsafe impl<T: FFISafe + Send> Send for RTBox<T> {}
-unsafe impl<T: FFISafe + Sync> Sync for RTBox<T> {}
+unsafe impl<T: ?Sized + Send> Send for RTBox<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for RTBox<T> {}
 
-impl<T: FFISafe> RTBox<T> {
-  pub fn new(data: T) -> Option<Self> {
-    // SAFETY:
-    //
-    // This implementation is defined safe.
+// Just like `Box`, moving the box never moves the data
+impl<T: ?Sized> Unpin for RTBox<T> {}
+
+impl<T: ?Sized> RTBox<T> {
+  /// Allocates the box with the header filled in, leaving the data uninitialized
+  unsafe fn alloc(
+    size: usize,
+    align: usize,
+    meta: usize,
+    vtable: &'static RTBoxVTable,
+    allocator: unsafe extern "C" fn(size: usize, align: usize) -> *mut c_void,
+  ) -> Option<NonNull<u8>> {
+    unsafe { Self::alloc_at(rt_data_offset(align), size, align, meta, vtable, allocator) }
+  }
+
+  /// Same as [`RTBox::alloc`], with the data placed at `offset`
+  unsafe fn alloc_at(
+    offset: usize,
+    size: usize,
+    align: usize,
+    meta: usize,
+    vtable: &'static RTBoxVTable,
+    allocator: unsafe extern "C" fn(size: usize, align: usize) -> *mut c_void,
+  ) -> Option<NonNull<u8>> {
     unsafe {
       // Use our own allocator
-      let out: *mut RTBoxWrapper<T> =
-        salloc::aligned_malloc(size_of::<RTBoxWrapper<T>>(), align_of::<RTBoxWrapper<T>>()) as _;
+      let base = allocator(offset + size, rt_alloc_align(align)) as *mut u8;
 
-      if out.is_null() {
+      if base.is_null() {
         return None;
       }
 
-      ptr::write(
-        out,
-        RTBoxWrapper {
-          _free: mfree::<T>,
-          _t: data,
-        },
-      );
+      let data = base.add(offset);
+
+      ptr::write(header_of(data), RTBoxWrapper::new(meta, vtable));
+
+      Some(NonNull::new_unchecked(data))
+    }
+  }
+
+  /// Boxes a sized value as an unsized type it coerces to, like `dyn Trait` or `[T]`
+  pub fn new_unsize<U: FFISafe + Unsize<T>>(data: U) -> Option<Self> {
+    Self::unsize_with(data, vtable::<U>())
+  }
+
+  /// Same as [`RTBox::new_unsize`], but the box can be cloned through
+  /// [`RTBox::try_clone`] without knowing `U`
+  pub fn new_unsize_cloneable<U: FFISafe + Clone + Unsize<T>>(data: U) -> Option<Self> {
+    Self::unsize_with(data, cloneable_vtable::<U>())
+  }
+
+  fn unsize_with<U: FFISafe + Unsize<T>>(data: U, vtable: &'static RTBoxVTable) -> Option<Self> {
+    // SAFETY:
+    //
+    // The metadata of the coerced pointer does not depend on the address
+    let meta = ptr::metadata(ptr::null::<U>() as *const T);
+
+    unsafe {
+      let ptr = Self::alloc(
+        size_of::<U>(),
+        align_of::<U>(),
+        meta_to_usize::<T>(meta),
+        vtable,
+        salloc::aligned_malloc,
+      )?;
+
+      ptr::write(ptr.as_ptr() as *mut U, data);
+
+      Some(Self {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+
+  #[inline(always)]
+  fn metadata(&self) -> <T as Pointee>::Metadata {
+    unsafe { usize_to_meta::<T>((*header_of(self.ptr.as_ptr()))._meta) }
+  }
+
+  /// Clones the box through the clone entry of its vtable
+  ///
+  /// This works without knowing the concrete type, e.g. for `RTBox<dyn Trait>`, as long
+  /// as the allocating side filled the entry in. Returns `None` if it did not or if the
+  /// allocation failed.
+  pub fn try_clone(&self) -> Option<Self> {
+    unsafe {
+      let clone = (*header_of(self.ptr.as_ptr()))._vtable.clone?;
 
       Some(Self {
-        ptr: NonNull::new(addr_of_mut!((*out)._t))?,
+        ptr: NonNull::new(clone(self.ptr.as_ptr() as _) as *mut u8)?,
+        _t: PhantomData,
       })
     }
   }
 
+  #[inline(always)]
+  /// Clones through the vtable, or with `fallback` when the box has no clone entry
+  fn clone_or(&self, fallback: impl FnOnce() -> Option<Self>) -> Self {
+    let clone = unsafe { (*header_of(self.ptr.as_ptr()))._vtable.clone };
+
+    let out = if clone.is_some() {
+      self.try_clone()
+    } else {
+      fallback()
+    };
+
+    match out {
+      Some(x) => x,
+      None => panic!("Allocation Failed"),
+    }
+  }
+
+  /// Pins the box, see [`RTBox::pin`]
+  pub fn into_pin(self) -> Pin<Self> {
+    // SAFETY: The data is never moved by the box itself
+    unsafe { Pin::new_unchecked(self) }
+  }
+
+  /// Gives up ownership, returning the pointer to the data
+  ///
+  /// The box must be given back to [`RTBox::from_raw`], possibly in another dylib,
+  /// to be freed.
   pub fn into_raw(self) -> *mut T {
+    let ptr = self.as_mut_ptr();
+    forget(self);
+
+    ptr
+  }
+
+  /// Gives up ownership without ever freeing the box, returning its data
+  pub fn leak<'a>(self) -> &'a mut T
+  where
+    T: 'a,
+  {
+    unsafe { &mut *self.into_raw() }
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`RTBox::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the box is dropped.
+  pub fn as_raw(&self) -> *const T {
+    self.as_ptr()
+  }
+
+  /// Lends the pointer of [`RTBox::into_raw`] to `f`, e.g. to hand it to foreign code
+  /// for the duration of a call
+  ///
+  /// `f` may read and write the data through it, but must neither free it nor keep it
+  /// after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut T) -> R) -> R {
+    f(self.as_mut_ptr())
+  }
+
+  /// Same as [`RTBox::into_raw`], but always returns the thin pointer to the data
+  ///
+  /// This is what should be handed to foreign code.
+  pub fn into_thin_raw(self) -> *mut c_void {
     let ptr = self.ptr;
     forget(self);
 
-    ptr.as_ptr()
+    ptr.as_ptr() as _
   }
 
   pub fn as_ptr(&self) -> *const T {
-    self.ptr.as_ptr() as _
+    ptr::from_raw_parts(self.ptr.as_ptr() as *const (), self.metadata())
   }
 
   pub fn as_mut_ptr(&self) -> *mut T {
-    self.ptr.as_ptr()
+    ptr::from_raw_parts_mut(self.ptr.as_ptr() as *mut (), self.metadata())
   }
 
   #[inline(always)]
-  /// Creates an RTBox from the associated pointer
+  /// Takes ownership of a pointer returned by [`RTBox::into_raw`], `None` if it is null
   ///
   /// # Safety
-  /// This pointer must have previously been provided by an RTBox wrapper
-  /// having the same SaFFI version. Failure to account for that will lead to Undefined Behaviour.
+  ///
+  /// The pointer must come from `into_raw` of an `RTBox<T>` built with the same saffi
+  /// version, and nobody else may own it. Failure to account for that will lead to
+  /// Undefined Behaviour.
   ///
   /// Pointers across boundaries can be legally used here.
+  #[track_caller]
   pub unsafe fn from_raw(data: *mut T) -> Option<Self> {
+    let ptr = NonNull::new(data as *mut u8)?;
+    unsafe { verify(ptr.as_ptr()) };
+
+    Some(Self {
+      ptr,
+      _t: PhantomData,
+    })
+  }
+
+  #[inline(always)]
+  /// Creates an RTBox from the thin pointer returned by [`RTBox::into_thin_raw`]
+  ///
+  /// # Safety
+  /// Same as [`RTBox::from_raw`], the box must also have been created for `T`.
+  #[track_caller]
+  pub unsafe fn from_thin_raw(data: *mut c_void) -> Option<Self> {
+    let ptr = NonNull::new(data as *mut u8)?;
+    unsafe { verify(ptr.as_ptr()) };
+
     Some(Self {
-      ptr: NonNull::new(data)?,
+      ptr,
+      _t: PhantomData,
     })
   }
+}
+
+impl<T: FFISafe> RTBox<T> {
+  pub fn new(data: T) -> Option<Self> {
+    Self::new_with(data, vtable::<T>())
+  }
+
+  /// Same as [`RTBox::new`], but foreign code can clone the box too, through [`saffi_rtbox_clone`](foreign::saffi_rtbox_clone)
+  pub fn new_cloneable(data: T) -> Option<Self>
+  where
+    T: Clone,
+  {
+    Self::new_with(data, cloneable_vtable::<T>())
+  }
+
+  fn new_with(data: T, vtable: &'static RTBoxVTable) -> Option<Self> {
+    // SAFETY:
+    //
+    // This implementation is defined safe.
+    unsafe {
+      let ptr = Self::alloc(
+        size_of::<T>(),
+        align_of::<T>(),
+        0,
+        vtable,
+        salloc::aligned_malloc,
+      )?;
+
+      ptr::write(ptr.as_ptr() as *mut T, data);
+
+      Some(Self {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+
+  /// Allocates the box without initializing its contents
+  ///
+  /// This allows building large values in place instead of moving them
+  /// from the stack, see [`RTBox::assume_init`] and [`RTBox::write`].
+  pub fn new_uninit() -> Option<RTBox<MaybeUninit<T>>> {
+    unsafe {
+      let ptr = RTBox::<MaybeUninit<T>>::alloc(
+        size_of::<T>(),
+        align_of::<T>(),
+        0,
+        vtable::<MaybeUninit<T>>(),
+        salloc::aligned_malloc,
+      )?;
+
+      Some(RTBox {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+
+  /// Same as [`RTBox::new_uninit`], but the memory is filled with zeroes
+  ///
+  /// This uses `salloc::aligned_zalloc`, which is usually cheaper than
+  /// writing the zeroes manually.
+  pub fn new_zeroed() -> Option<RTBox<MaybeUninit<T>>> {
+    unsafe {
+      let ptr = RTBox::<MaybeUninit<T>>::alloc(
+        size_of::<T>(),
+        align_of::<T>(),
+        0,
+        vtable::<MaybeUninit<T>>(),
+        salloc::aligned_zalloc,
+      )?;
+
+      Some(RTBox {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+
+  /// Boxes the value and pins it in place
+  ///
+  /// The data of an RTBox never moves while the box is alive, moving the box only
+  /// moves the pointer. So as long as the box is not unboxed, which needs `unsafe`
+  /// code on a pinned box anyway, `T` is never moved until it is dropped, even if
+  /// the box is dropped by another dylib.
+  pub fn pin(data: T) -> Option<Pin<Self>> {
+    Some(Self::new(data)?.into_pin())
+  }
+
+  /// Moves the value out of the box, freeing the allocation
+  ///
+  /// # Ownership
+  ///
+  /// The box must be exclusively owned, which holds for any `RTBox` obtained
+  /// through safe code or through [`RTBox::from_raw`] with its safety contract upheld.
+  /// The allocation is released through the `dealloc` function of the box, i.e. by the
+  /// dylib that allocated it, so the box may come from anywhere.
+  ///
+  /// Boxes whose data may only be destroyed by their own `free` function, have no
+  /// `dealloc` and are handed back as `Err`. Those can only be dropped.
+  pub fn into_inner(self) -> Result<T, Self> {
+    let ptr = self.ptr;
+
+    unsafe {
+      let Some(dealloc) = (*header_of(ptr.as_ptr()))._vtable.dealloc else {
+        return Err(self);
+      };
+
+      forget(self);
+
+      let out = ptr::read(ptr.as_ptr() as *mut T);
+      dealloc(ptr.as_ptr() as _);
+
+      Ok(out)
+    }
+  }
+
+  /// Takes the value out of the box, leaving `T::default()` in its place
+  ///
+  /// Unlike [`RTBox::into_inner`], the box stays alive, so this works for every box.
+  pub fn take(&mut self) -> T
+  where
+    T: Default,
+  {
+    std::mem::take(&mut **self)
+  }
+
+  /// Replaces the value in the box, returning the old one
+  pub fn replace(&mut self, value: T) -> T {
+    std::mem::replace(&mut **self, value)
+  }
 
   /// Safety:
   ///
-  /// Safety constraints cannot be displayed for deprecated, potentially dangerous
-  /// functions.
-  #[deprecated(
-    since = "0.1.0",
-    note = "Use of this method is highly discouraged due to potential (disastrous) consequences"
-  )]
+  /// The box must have a `dealloc` function, see [`RTBox::into_inner`]
+  #[deprecated(since = "0.1.0", note = "Use `RTBox::into_inner` instead")]
   pub unsafe fn unbox(self) -> T {
+    match self.into_inner() {
+      Ok(out) => out,
+      Err(_) => panic!("This box cannot be unboxed"),
+    }
+  }
+}
+
+impl<T: FFISafe> RTBox<MaybeUninit<T>> {
+  /// Converts to `RTBox<T>`
+  ///
+  /// The header is updated to drop `T` from now on.
+  ///
+  /// # Safety
+  ///
+  /// The value must have been completely initialized, see [`MaybeUninit::assume_init`]
+  pub unsafe fn assume_init(self) -> RTBox<T> {
     let ptr = self.ptr;
+    forget(self);
+
+    unsafe {
+      (*header_of(ptr.as_ptr()))._vtable = vtable::<T>();
+    }
+
+    RTBox {
+      ptr,
+      _t: PhantomData,
+    }
+  }
+
+  /// Writes the value and converts to `RTBox<T>`
+  pub fn write(mut self, value: T) -> RTBox<T> {
+    (*self).write(value);
+
+    // SAFETY: It was just initialized
+    unsafe { self.assume_init() }
+  }
+}
+
+impl<T: FFISafe> RTBox<[T]> {
+  pub fn new_slice(data: &[T]) -> Option<Self>
+  where
+    T: Copy,
+  {
+    unsafe {
+      let ptr = Self::alloc(
+        size_of_val(data),
+        align_of::<T>(),
+        data.len(),
+        cloneable_vtable_slice::<T>(),
+        salloc::aligned_malloc,
+      )?;
+
+      ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr() as *mut T, data.len());
+
+      Some(Self {
+        ptr,
+        _t: PhantomData,
+      })
+    }
+  }
+}
 
-    let out = unsafe { ptr::read(ptr.as_ptr()) };
+impl<T: FFISafe> From<Vector<T>> for RTBox<[T]> {
+  /// This reuses the allocation of the vector, since the header of a `Vector<T>`
+  /// takes the exact place of the [`RTBoxWrapper`]
+  fn from(value: Vector<T>) -> Self {
+    let len = value.len();
 
-    // Deallocate shim
     unsafe {
-      let base_ptr = (self.ptr.as_ptr() as *mut u8).byte_offset(rt_header_offset::<T>());
-      salloc::aligned_free(base_ptr as _);
+      let ptr = NonNull::new_unchecked(value.into_raw() as *mut u8);
+
+      ptr::write(
+        header_of(ptr.as_ptr()),
+        RTBoxWrapper::new(len, vtable_slice::<T>()),
+      );
+
+      Self {
+        ptr,
+        _t: PhantomData,
+      }
     }
+  }
+}
 
-    forget(self);
+impl RTBox<str> {
+  pub fn new_str(data: &str) -> Option<Self> {
+    let bytes = RTBox::<[u8]>::new_slice(data.as_bytes())?;
+
+    // SAFETY: The metadata of `str` and `[u8]` is the same
+    Some(unsafe { RTBox::from_thin_raw(bytes.into_thin_raw())? })
+  }
+}
 
-    out
+impl From<SharableStr> for RTBox<str> {
+  /// This reuses the allocation of the string
+  fn from(value: SharableStr) -> Self {
+    let bytes = RTBox::<[u8]>::from(value.into_bytes());
+
+    // Strings can always be cloned, like the ones of `new_str`
+    unsafe { (*header_of(bytes.ptr.as_ptr()))._vtable = cloneable_vtable_slice::<u8>() };
+
+    // SAFETY: The bytes were valid UTF-8 & the metadata of `str` and `[u8]` is the same
+    unsafe { RTBox::from_thin_raw(bytes.into_thin_raw()).unwrap_unchecked() }
+  }
+}
+
+/// The clone goes through the header when the box has a clone entry, so it is
+/// allocated the same way as the box itself. Otherwise, e.g. for a box of
+/// [`RTBox::new`], the value is cloned into a new box of [`RTBox::new_cloneable`].
+impl<T: FFISafe + Clone> Clone for RTBox<T> {
+  fn clone(&self) -> Self {
+    self.clone_or(|| RTBox::new_cloneable((**self).clone()))
+  }
+}
+
+impl<T: FFISafe + Clone> Clone for RTBox<[T]> {
+  fn clone(&self) -> Self {
+    self.clone_or(|| {
+      let mut vector = Vector::new();
+      vector.extend(self.iter().cloned());
+
+      let out = RTBox::from(vector);
+      unsafe { (*header_of(out.ptr.as_ptr()))._vtable = cloneable_vtable_slice::<T>() };
+
+      Some(out)
+    })
+  }
+}
+
+impl Clone for RTBox<str> {
+  fn clone(&self) -> Self {
+    self.clone_or(|| RTBox::new_str(self))
   }
 }
 
-impl<T: FFISafe + Sized> Deref for RTBox<T> {
+impl<T: ?Sized> Deref for RTBox<T> {
   type Target = T;
 
   fn deref(&self) -> &Self::Target {
+    unsafe { verify(self.ptr.as_ptr()) };
+
     // SAFETY: Using this is technically safe because RTBox
     // is immutably borrowed, so safe code cannot
     // trigger a UAF or
@@ -121,8 +676,10 @@ impl<T: FFISafe + Sized> Deref for RTBox<T> {
   }
 }
 
-impl<T: FFISafe + Sized> DerefMut for RTBox<T> {
+impl<T: ?Sized> DerefMut for RTBox<T> {
   fn deref_mut(&mut self) -> &mut Self::Target {
+    unsafe { verify(self.ptr.as_ptr()) };
+
     // SAFETY: Using this is technically safe because RTBox
     // is mutably borrowed, so safe code cannot
     // trigger errors
@@ -130,31 +687,106 @@ impl<T: FFISafe + Sized> DerefMut for RTBox<T> {
   }
 }
 
-/// Negative offset from T back to the Header:
-const fn rt_header_offset<T: FFISafe>() -> isize {
-  -(offset_of!(RTBoxWrapper<T>, _t) as isize)
+// Internal machinery to maintain owner.
+#[inline(always)]
+/// Poisons the header and `count` elements of `T`, see the `hardened` feature
+pub(crate) unsafe fn poison<T>(data: *mut c_void, count: usize) {
+  unsafe {
+    hardened::poison(
+      header_of(data as _) as _,
+      size_of::<RTBoxWrapper>() + size_of::<T>() * count,
+    )
+  };
 }
 
-// Internal machinery to maintain owner.
 unsafe extern "C" fn mfree<T: FFISafe>(data: *mut c_void) {
   unsafe {
     ptr::drop_in_place(data as *mut T);
+    poison::<T>(data, 1);
+
+    let base_ptr = (data as *mut u8).sub(rt_data_offset(align_of::<T>()));
+    salloc::aligned_free(base_ptr as _);
+  }
+}
+
+unsafe extern "C" fn mdealloc<T: FFISafe>(data: *mut c_void) {
+  unsafe {
+    poison::<T>(data, 1);
+
+    let base_ptr = (data as *mut u8).sub(rt_data_offset(align_of::<T>()));
+    salloc::aligned_free(base_ptr as _);
+  }
+}
+
+unsafe extern "C" fn mclone<T: FFISafe + Clone>(data: *const c_void) -> *mut c_void {
+  unsafe {
+    // Keep the metadata & vtable, `T` may have been unsized
+    let header = &*header_of(data as _);
+
+    let Some(ptr) = RTBox::<T>::alloc(
+      size_of::<T>(),
+      align_of::<T>(),
+      header._meta,
+      header._vtable,
+      salloc::aligned_malloc,
+    ) else {
+      return ptr::null_mut();
+    };
 
-    let base_ptr = (data as *mut u8).byte_offset(rt_header_offset::<T>());
+    ptr::write(ptr.as_ptr() as *mut T, (*(data as *const T)).clone());
+
+    ptr.as_ptr() as _
+  }
+}
+
+unsafe extern "C" fn mclone_slice<T: FFISafe + Clone>(data: *const c_void) -> *mut c_void {
+  unsafe {
+    let header = &*header_of(data as _);
+    let len = header._meta;
+
+    let Some(ptr) = RTBox::<[T]>::alloc(
+      size_of::<T>() * len,
+      align_of::<T>(),
+      len,
+      header._vtable,
+      salloc::aligned_malloc,
+    ) else {
+      return ptr::null_mut();
+    };
+
+    let src = data as *const T;
+    let dst = ptr.as_ptr() as *mut T;
+
+    for i in 0..len {
+      ptr::write(dst.add(i), (*src.add(i)).clone());
+    }
+
+    ptr.as_ptr() as _
+  }
+}
+
+unsafe extern "C" fn mfree_slice<T: FFISafe>(data: *mut c_void) {
+  unsafe {
+    let len = (*header_of(data as _))._meta;
+    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(data as *mut T, len));
+    poison::<T>(data, len);
+
+    let base_ptr = (data as *mut u8).sub(rt_data_offset(align_of::<T>()));
     salloc::aligned_free(base_ptr as _);
   }
 }
 
-impl<T: FFISafe + Sized> Drop for RTBox<T> {
+impl<T: ?Sized> Drop for RTBox<T> {
   fn drop(&mut self) {
     // SAFETY:
     // Since it is owned by the context
     // It is safe to access pointer fields.
     unsafe {
-      let header_ptr =
-        self.ptr.as_ptr().byte_offset(rt_header_offset::<T>()) as *mut RTBoxWrapper<T>;
+      verify(self.ptr.as_ptr());
+
+      let header_ptr = header_of(self.ptr.as_ptr());
 
-      ((*header_ptr)._free)(self.ptr.as_ptr() as _);
+      ((*header_ptr)._vtable.free)(self.ptr.as_ptr() as _);
     }
   }
 }
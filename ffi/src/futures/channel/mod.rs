@@ -0,0 +1,315 @@
+//! Async channels whose halves can be sent to & used from any dylib
+//!
+//! The shared state is allocated through salloc and every waker is stored together
+//! with the [`WakerVTable`] of the dylib that registered it, so it is always woken
+//! & freed by the code that created it. Like every other saffi type, both sides must
+//! use the same saffi version.
+
+use core::{
+  cell::UnsafeCell,
+  fmt,
+  mem::{self, ManuallyDrop},
+  ops::{Deref, DerefMut},
+  ptr::{self, NonNull},
+};
+use std::{
+  hint::spin_loop,
+  sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+  task::Waker,
+  thread::yield_now,
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::{CWaker, WAKER_VTABLE, WakerVTable},
+  vector::Vector,
+};
+
+pub mod mpsc;
+pub mod oneshot;
+pub mod watch;
+
+/// The receiving half was dropped, this is the value that could not be sent
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("SendError(..)")
+  }
+}
+
+pub enum TrySendError<T> {
+  /// The channel is at capacity
+  Full(T),
+  /// The receiving half was dropped
+  Closed(T),
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Full(_) => f.write_str("Full(..)"),
+      Self::Closed(_) => f.write_str("Closed(..)"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+  /// Nothing was sent yet
+  Empty,
+  /// Nothing was sent & every sending half was dropped
+  Disconnected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The other half of the channel was dropped
+pub struct Closed;
+
+#[repr(C)]
+struct SpinLock<T> {
+  locked: AtomicBool,
+  value: UnsafeCell<T>,
+}
+
+impl<T> SpinLock<T> {
+  const fn new(value: T) -> Self {
+    Self {
+      locked: AtomicBool::new(false),
+      value: UnsafeCell::new(value),
+    }
+  }
+
+  /// Every critical section is a few instructions long, so this does not park
+  fn lock(&self) -> SpinGuard<'_, T> {
+    let mut iterations: u32 = 0;
+
+    while self
+      .locked
+      .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+      .is_err()
+    {
+      iterations += 1;
+
+      if iterations.is_multiple_of(20) {
+        iterations = 0;
+        yield_now();
+      } else {
+        spin_loop();
+      }
+    }
+
+    SpinGuard { lock: self }
+  }
+}
+
+struct SpinGuard<'a, T> {
+  lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinGuard<'_, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    unsafe { &*self.lock.value.get() }
+  }
+}
+
+impl<T> DerefMut for SpinGuard<'_, T> {
+  fn deref_mut(&mut self) -> &mut T {
+    unsafe { &mut *self.lock.value.get() }
+  }
+}
+
+impl<T> Drop for SpinGuard<'_, T> {
+  fn drop(&mut self) {
+    self.lock.locked.store(false, Ordering::Release);
+  }
+}
+
+#[repr(C)]
+/// A waker together with the vtable of the dylib which registered it
+struct FfiWaker {
+  waker: CWaker,
+  vtable: &'static WakerVTable,
+}
+
+unsafe impl FFISafe for FfiWaker {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl FfiWaker {
+  fn new(waker: &Waker) -> Self {
+    let waker = ManuallyDrop::new(waker.clone());
+
+    Self {
+      waker: CWaker {
+        data: waker.data(),
+        vtable: waker.vtable() as *const _ as _,
+      },
+      vtable: &WAKER_VTABLE,
+    }
+  }
+
+  fn will_wake(&self, waker: &Waker) -> bool {
+    ptr::eq(self.waker.data, waker.data())
+      && ptr::eq(self.waker.vtable, waker.vtable() as *const _ as _)
+  }
+
+  fn wake(self) {
+    let this = ManuallyDrop::new(self);
+
+    unsafe { (this.vtable.wake_and_free)(this.waker) };
+  }
+}
+
+impl Drop for FfiWaker {
+  fn drop(&mut self) {
+    unsafe { (self.vtable.free_waker)(self.waker) };
+  }
+}
+
+#[repr(C)]
+/// An `Option` with a defined layout, since the shared states are read by every dylib
+enum FfiOption<T> {
+  None,
+  Some(T),
+}
+
+impl<T> FfiOption<T> {
+  fn take(&mut self) -> Option<T> {
+    match mem::replace(self, Self::None) {
+      Self::Some(x) => Some(x),
+      Self::None => None,
+    }
+  }
+
+  fn as_ref(&self) -> Option<&T> {
+    match self {
+      Self::Some(x) => Some(x),
+      Self::None => None,
+    }
+  }
+
+  fn get_or_insert_with(&mut self, f: impl FnOnce() -> T) -> &mut T {
+    if let Self::None = self {
+      *self = Self::Some(f());
+    }
+
+    match self {
+      Self::Some(x) => x,
+      Self::None => unreachable!(),
+    }
+  }
+}
+
+#[repr(C)]
+/// Holds the waker of the single task waiting on one side of a channel
+struct WakerSlot {
+  waker: FfiOption<FfiWaker>,
+}
+
+impl WakerSlot {
+  const fn new() -> Self {
+    Self {
+      waker: FfiOption::None,
+    }
+  }
+
+  fn register(&mut self, waker: &Waker) {
+    if !self.waker.as_ref().is_some_and(|w| w.will_wake(waker)) {
+      self.waker = FfiOption::Some(FfiWaker::new(waker));
+    }
+  }
+
+  /// Wake the returned waker only after unlocking
+  fn take(&mut self) -> Option<FfiWaker> {
+    self.waker.take()
+  }
+}
+
+#[repr(C)]
+/// The wakers of every task waiting on one side of a channel
+struct WakerList {
+  wakers: FfiOption<Vector<FfiWaker>>,
+}
+
+impl WakerList {
+  const fn new() -> Self {
+    Self {
+      wakers: FfiOption::None,
+    }
+  }
+
+  fn register(&mut self, waker: &Waker) {
+    let wakers = self.wakers.get_or_insert_with(Vector::new);
+
+    if !wakers.iter().any(|w| w.will_wake(waker)) {
+      wakers.push(FfiWaker::new(waker));
+    }
+  }
+
+  /// Wake the returned wakers only after unlocking
+  fn take(&mut self) -> Option<Vector<FfiWaker>> {
+    self.wakers.take()
+  }
+}
+
+fn wake_all(wakers: Option<Vector<FfiWaker>>) {
+  if let Some(mut wakers) = wakers {
+    while let Some(waker) = wakers.pop() {
+      waker.wake();
+    }
+  }
+}
+
+#[repr(C)]
+/// The state of a channel, shared by every half of it
+struct Shared<T> {
+  /// Number of halves alive
+  refs: AtomicUsize,
+  inner: SpinLock<T>,
+}
+
+impl<T> Shared<T> {
+  fn alloc(inner: T) -> NonNull<Self> {
+    let ptr = unsafe {
+      salloc::aligned_malloc(
+        size_of::<Self>(),
+        align_of::<Self>().max(size_of::<*const ()>()),
+      )
+    } as *mut Self;
+
+    let Some(ptr) = NonNull::new(ptr) else {
+      panic!("Allocation Failed");
+    };
+
+    unsafe {
+      ptr.write(Self {
+        refs: AtomicUsize::new(1),
+        inner: SpinLock::new(inner),
+      })
+    };
+
+    ptr
+  }
+
+  fn acquire(&self) {
+    self.refs.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// # Safety
+  ///
+  /// Every half releases its reference exactly once, then never touches the state again
+  unsafe fn release(ptr: NonNull<Self>) {
+    if unsafe { ptr.as_ref() }.refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+      unsafe {
+        ptr::drop_in_place(ptr.as_ptr());
+        salloc::aligned_free(ptr.as_ptr() as _);
+      }
+    }
+  }
+}
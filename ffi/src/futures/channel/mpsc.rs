@@ -0,0 +1,379 @@
+use core::{
+  future::poll_fn,
+  pin::Pin,
+  ptr::NonNull,
+  task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::channel::{
+    Closed, SendError, Shared, TryRecvError, TrySendError, WakerList, WakerSlot, wake_all,
+  },
+  vector::deque::FfiVecDeque,
+};
+
+#[repr(C)]
+struct Inner<T: FFISafe> {
+  buffer: FfiVecDeque<T>,
+  cap: usize,
+  /// Slots promised to a [`FfiSink`] that did not send yet
+  reserved: usize,
+
+  senders: usize,
+  receiver: bool,
+
+  rx_waker: WakerSlot,
+  /// Every sender waiting for a free slot is woken once one is free, since the one we
+  /// would pick might not be polled ever again
+  tx_wakers: WakerList,
+}
+
+impl<T: FFISafe> Inner<T> {
+  fn is_full(&self) -> bool {
+    self.buffer.len() + self.reserved >= self.cap
+  }
+}
+
+/// Creates a bounded channel, sending waits while `cap` values are queued
+///
+/// # Panics
+///
+/// If `cap` is 0
+pub fn channel<T: FFISafe>(cap: usize) -> (Sender<T>, Receiver<T>) {
+  assert!(cap > 0, "mpsc channel capacity must be at least 1");
+
+  let shared = Shared::alloc(Inner {
+    buffer: FfiVecDeque::new(),
+    cap,
+    reserved: 0,
+    senders: 1,
+    receiver: true,
+    rx_waker: WakerSlot::new(),
+    tx_wakers: WakerList::new(),
+  });
+  unsafe { shared.as_ref() }.acquire();
+
+  (Sender { shared }, Receiver { shared })
+}
+
+#[repr(C)]
+pub struct Sender<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+}
+
+#[repr(C)]
+pub struct Receiver<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+}
+
+unsafe impl<T: FFISafe> FFISafe for Sender<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe> FFISafe for Receiver<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send> Send for Sender<T> {}
+unsafe impl<T: FFISafe + Send> Sync for Sender<T> {}
+unsafe impl<T: FFISafe + Send> Send for Receiver<T> {}
+unsafe impl<T: FFISafe + Send> Sync for Receiver<T> {}
+
+impl<T: FFISafe> Sender<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  /// Sends without waiting, failing if the channel is full or closed
+  pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+    match self.send_or_register(value, None) {
+      Ok(()) => Ok(()),
+      Err((value, true)) => Err(TrySendError::Closed(value)),
+      Err((value, false)) => Err(TrySendError::Full(value)),
+    }
+  }
+
+  /// Sends, waiting for a free slot while the channel is full
+  pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+    let mut value = Some(value);
+
+    poll_fn(|cx| {
+      let Some(item) = value.take() else {
+        unreachable!();
+      };
+
+      match self.send_or_register(item, Some(cx)) {
+        Ok(()) => Poll::Ready(Ok(())),
+        Err((item, true)) => Poll::Ready(Err(SendError(item))),
+        Err((item, false)) => {
+          value = Some(item);
+          Poll::Pending
+        }
+      }
+    })
+    .await
+  }
+
+  /// Returns the value and whether the channel is closed if it could not be sent,
+  /// registering the waker while it is full
+  fn send_or_register(&self, value: T, cx: Option<&mut Context<'_>>) -> Result<(), (T, bool)> {
+    let mut inner = self.shared().inner.lock();
+
+    if !inner.receiver {
+      return Err((value, true));
+    }
+
+    if inner.is_full() {
+      if let Some(cx) = cx {
+        inner.tx_wakers.register(cx.waker());
+      }
+
+      return Err((value, false));
+    }
+
+    inner.buffer.push_back(value);
+    let waker = inner.rx_waker.take();
+    drop(inner);
+
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+
+    Ok(())
+  }
+
+  /// Reserves a slot for a value sent later through [`Sender::send_reserved`]
+  fn poll_reserve(&self, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+    let mut inner = self.shared().inner.lock();
+
+    if !inner.receiver {
+      return Poll::Ready(Err(Closed));
+    }
+
+    if inner.is_full() {
+      inner.tx_wakers.register(cx.waker());
+
+      return Poll::Pending;
+    }
+
+    inner.reserved += 1;
+
+    Poll::Ready(Ok(()))
+  }
+
+  fn send_reserved(&self, value: T) -> Result<(), Closed> {
+    let mut inner = self.shared().inner.lock();
+    inner.reserved -= 1;
+
+    if !inner.receiver {
+      return Err(Closed);
+    }
+
+    inner.buffer.push_back(value);
+    let waker = inner.rx_waker.take();
+    drop(inner);
+
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+
+    Ok(())
+  }
+
+  fn cancel_reserved(&self) {
+    let mut inner = self.shared().inner.lock();
+    inner.reserved -= 1;
+
+    let wakers = inner.tx_wakers.take();
+    drop(inner);
+
+    wake_all(wakers);
+  }
+
+  /// Whether the receiving half was dropped
+  pub fn is_closed(&self) -> bool {
+    !self.shared().inner.lock().receiver
+  }
+}
+
+impl<T: FFISafe> Clone for Sender<T> {
+  fn clone(&self) -> Self {
+    self.shared().inner.lock().senders += 1;
+    self.shared().acquire();
+
+    Self {
+      shared: self.shared,
+    }
+  }
+}
+
+impl<T: FFISafe> Drop for Sender<T> {
+  fn drop(&mut self) {
+    let mut inner = self.shared().inner.lock();
+    inner.senders -= 1;
+
+    let waker = if inner.senders == 0 {
+      inner.rx_waker.take()
+    } else {
+      None
+    };
+    drop(inner);
+
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
+
+impl<T: FFISafe> Receiver<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+    match self.recv_or_register(None) {
+      Poll::Ready(Some(value)) => Ok(value),
+      Poll::Ready(None) => Err(TryRecvError::Disconnected),
+      Poll::Pending => Err(TryRecvError::Empty),
+    }
+  }
+
+  /// Receives the next value, `None` once every sender is dropped & the channel is empty
+  pub async fn recv(&mut self) -> Option<T> {
+    poll_fn(|cx| self.poll_recv(cx)).await
+  }
+
+  pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+    self.recv_or_register(Some(cx))
+  }
+
+  fn recv_or_register(&mut self, cx: Option<&mut Context<'_>>) -> Poll<Option<T>> {
+    let mut inner = self.shared().inner.lock();
+
+    if let Some(value) = inner.buffer.pop_front() {
+      let wakers = inner.tx_wakers.take();
+      drop(inner);
+
+      wake_all(wakers);
+
+      return Poll::Ready(Some(value));
+    }
+
+    if inner.senders == 0 {
+      return Poll::Ready(None);
+    }
+
+    if let Some(cx) = cx {
+      inner.rx_waker.register(cx.waker());
+    }
+
+    Poll::Pending
+  }
+}
+
+impl<T: FFISafe> Stream for Receiver<T> {
+  type Item = T;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+    self.get_mut().poll_recv(cx)
+  }
+}
+
+impl<T: FFISafe> Drop for Receiver<T> {
+  fn drop(&mut self) {
+    let mut inner = self.shared().inner.lock();
+    inner.receiver = false;
+
+    let wakers = inner.tx_wakers.take();
+    drop(inner);
+
+    wake_all(wakers);
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
+
+/// A [`Sink`] over a [`Sender`]
+///
+/// `poll_ready` reserves a slot in the channel, which the next `start_send` fills.
+/// Values are delivered as soon as they are sent, so flushing does nothing.
+pub struct FfiSink<T: FFISafe> {
+  sender: Sender<T>,
+  reserved: bool,
+}
+
+impl<T: FFISafe> FfiSink<T> {
+  pub const fn new(sender: Sender<T>) -> Self {
+    Self {
+      sender,
+      reserved: false,
+    }
+  }
+
+  pub fn into_inner(mut self) -> Sender<T> {
+    if self.reserved {
+      self.reserved = false;
+      self.sender.cancel_reserved();
+    }
+
+    let this = core::mem::ManuallyDrop::new(self);
+
+    unsafe { core::ptr::read(&this.sender) }
+  }
+}
+
+impl<T: FFISafe> Sink<T> for FfiSink<T> {
+  type Error = Closed;
+
+  fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+    let this = self.get_mut();
+
+    if this.reserved {
+      return Poll::Ready(Ok(()));
+    }
+
+    let out = this.sender.poll_reserve(cx);
+    this.reserved = matches!(out, Poll::Ready(Ok(())));
+
+    out
+  }
+
+  fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Closed> {
+    let this = self.get_mut();
+
+    assert!(
+      this.reserved,
+      "FfiSink::start_send called before poll_ready"
+    );
+    this.reserved = false;
+
+    this.sender.send_reserved(item)
+  }
+
+  fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+    Poll::Ready(Ok(()))
+  }
+
+  fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl<T: FFISafe> Drop for FfiSink<T> {
+  fn drop(&mut self) {
+    if self.reserved {
+      self.sender.cancel_reserved();
+    }
+  }
+}
@@ -0,0 +1,153 @@
+use core::{
+  future::Future,
+  pin::Pin,
+  ptr::NonNull,
+  task::{Context, Poll},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::channel::{Closed, FfiOption, Shared, TryRecvError, WakerSlot},
+};
+
+#[repr(C)]
+struct Inner<T> {
+  value: FfiOption<T>,
+
+  sender: bool,
+  receiver: bool,
+
+  rx_waker: WakerSlot,
+}
+
+/// Creates a channel for a single value
+pub fn channel<T: FFISafe>() -> (Sender<T>, Receiver<T>) {
+  let shared = Shared::alloc(Inner {
+    value: FfiOption::None,
+    sender: true,
+    receiver: true,
+    rx_waker: WakerSlot::new(),
+  });
+  unsafe { shared.as_ref() }.acquire();
+
+  (Sender { shared }, Receiver { shared })
+}
+
+#[repr(C)]
+pub struct Sender<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+}
+
+#[repr(C)]
+/// Awaiting it resolves to the value, or `Err` if the sender was dropped without sending
+pub struct Receiver<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+}
+
+unsafe impl<T: FFISafe> FFISafe for Sender<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe> FFISafe for Receiver<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send> Send for Sender<T> {}
+unsafe impl<T: FFISafe + Send> Sync for Sender<T> {}
+unsafe impl<T: FFISafe + Send> Send for Receiver<T> {}
+unsafe impl<T: FFISafe + Send> Sync for Receiver<T> {}
+
+impl<T: FFISafe> Sender<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  /// Sends the value, giving it back if the receiver was dropped
+  pub fn send(self, value: T) -> Result<(), T> {
+    let mut inner = self.shared().inner.lock();
+
+    if !inner.receiver {
+      return Err(value);
+    }
+
+    inner.value = FfiOption::Some(value);
+    let waker = inner.rx_waker.take();
+    drop(inner);
+
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+
+    Ok(())
+  }
+
+  /// Whether the receiver was dropped
+  pub fn is_closed(&self) -> bool {
+    !self.shared().inner.lock().receiver
+  }
+}
+
+impl<T: FFISafe> Drop for Sender<T> {
+  fn drop(&mut self) {
+    let mut inner = self.shared().inner.lock();
+    inner.sender = false;
+
+    let waker = inner.rx_waker.take();
+    drop(inner);
+
+    if let Some(waker) = waker {
+      waker.wake();
+    }
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
+
+impl<T: FFISafe> Receiver<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+    let mut inner = self.shared().inner.lock();
+
+    match inner.value.take() {
+      Some(value) => Ok(value),
+      None if inner.sender => Err(TryRecvError::Empty),
+      None => Err(TryRecvError::Disconnected),
+    }
+  }
+}
+
+impl<T: FFISafe> Future for Receiver<T> {
+  type Output = Result<T, Closed>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let mut inner = self.shared().inner.lock();
+
+    if let Some(value) = inner.value.take() {
+      return Poll::Ready(Ok(value));
+    }
+
+    if !inner.sender {
+      return Poll::Ready(Err(Closed));
+    }
+
+    inner.rx_waker.register(cx.waker());
+
+    Poll::Pending
+  }
+}
+
+impl<T: FFISafe> Drop for Receiver<T> {
+  fn drop(&mut self) {
+    self.shared().inner.lock().receiver = false;
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
@@ -0,0 +1,233 @@
+use core::{
+  future::poll_fn,
+  mem,
+  ops::Deref,
+  ptr::NonNull,
+  task::{Context, Poll},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::channel::{Closed, SendError, Shared, SpinGuard, WakerList, wake_all},
+};
+
+#[repr(C)]
+struct Inner<T> {
+  value: T,
+  /// Bumped on every send
+  version: u64,
+
+  senders: usize,
+  receivers: usize,
+
+  rx_wakers: WakerList,
+}
+
+/// Creates a channel which only keeps the last value sent, starting with `init`
+pub fn channel<T: FFISafe>(init: T) -> (Sender<T>, Receiver<T>) {
+  let shared = Shared::alloc(Inner {
+    value: init,
+    version: 0,
+    senders: 1,
+    receivers: 1,
+    rx_wakers: WakerList::new(),
+  });
+  unsafe { shared.as_ref() }.acquire();
+
+  (Sender { shared }, Receiver { shared, seen: 0 })
+}
+
+#[repr(C)]
+pub struct Sender<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+}
+
+#[repr(C)]
+pub struct Receiver<T: FFISafe> {
+  shared: NonNull<Shared<Inner<T>>>,
+  /// Version of the last value seen
+  seen: u64,
+}
+
+unsafe impl<T: FFISafe> FFISafe for Sender<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe> FFISafe for Receiver<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send> Send for Sender<T> {}
+unsafe impl<T: FFISafe + Send + Sync> Sync for Sender<T> {}
+unsafe impl<T: FFISafe + Send> Send for Receiver<T> {}
+unsafe impl<T: FFISafe + Send + Sync> Sync for Receiver<T> {}
+
+/// The current value of a watch channel
+///
+/// This holds the lock of the channel, so it must not be kept around.
+pub struct Ref<'a, T> {
+  guard: SpinGuard<'a, Inner<T>>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.guard.value
+  }
+}
+
+impl<T: FFISafe> Sender<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  /// Replaces the value, failing if every receiver was dropped
+  pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+    if self.shared().inner.lock().receivers == 0 {
+      return Err(SendError(value));
+    }
+
+    drop(self.send_replace(value));
+
+    Ok(())
+  }
+
+  /// Replaces the value even if nobody receives it, returning the previous one
+  pub fn send_replace(&self, value: T) -> T {
+    let mut inner = self.shared().inner.lock();
+
+    let old = mem::replace(&mut inner.value, value);
+    inner.version += 1;
+
+    let wakers = inner.rx_wakers.take();
+    drop(inner);
+
+    wake_all(wakers);
+
+    old
+  }
+
+  pub fn borrow(&self) -> Ref<'_, T> {
+    Ref {
+      guard: self.shared().inner.lock(),
+    }
+  }
+
+  pub fn subscribe(&self) -> Receiver<T> {
+    let mut inner = self.shared().inner.lock();
+    inner.receivers += 1;
+
+    let seen = inner.version;
+    drop(inner);
+
+    self.shared().acquire();
+
+    Receiver {
+      shared: self.shared,
+      seen,
+    }
+  }
+}
+
+impl<T: FFISafe> Clone for Sender<T> {
+  fn clone(&self) -> Self {
+    self.shared().inner.lock().senders += 1;
+    self.shared().acquire();
+
+    Self {
+      shared: self.shared,
+    }
+  }
+}
+
+impl<T: FFISafe> Drop for Sender<T> {
+  fn drop(&mut self) {
+    let mut inner = self.shared().inner.lock();
+    inner.senders -= 1;
+
+    let wakers = if inner.senders == 0 {
+      inner.rx_wakers.take()
+    } else {
+      None
+    };
+    drop(inner);
+
+    wake_all(wakers);
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
+
+impl<T: FFISafe> Receiver<T> {
+  #[inline(always)]
+  fn shared(&self) -> &Shared<Inner<T>> {
+    unsafe { self.shared.as_ref() }
+  }
+
+  pub fn borrow(&self) -> Ref<'_, T> {
+    Ref {
+      guard: self.shared().inner.lock(),
+    }
+  }
+
+  /// Same as [`Receiver::borrow`], but marks the value as seen
+  pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+    let guard = unsafe { self.shared.as_ref() }.inner.lock();
+    self.seen = guard.version;
+
+    Ref { guard }
+  }
+
+  /// Whether a value was sent since the last one seen
+  pub fn has_changed(&self) -> bool {
+    self.shared().inner.lock().version != self.seen
+  }
+
+  /// Waits for a value not seen yet & marks it as seen, failing once every sender is dropped
+  pub async fn changed(&mut self) -> Result<(), Closed> {
+    poll_fn(|cx| self.poll_changed(cx)).await
+  }
+
+  pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Closed>> {
+    let mut inner = unsafe { self.shared.as_ref() }.inner.lock();
+
+    if inner.version != self.seen {
+      self.seen = inner.version;
+
+      return Poll::Ready(Ok(()));
+    }
+
+    if inner.senders == 0 {
+      return Poll::Ready(Err(Closed));
+    }
+
+    inner.rx_wakers.register(cx.waker());
+
+    Poll::Pending
+  }
+}
+
+impl<T: FFISafe> Clone for Receiver<T> {
+  fn clone(&self) -> Self {
+    self.shared().inner.lock().receivers += 1;
+    self.shared().acquire();
+
+    Self {
+      shared: self.shared,
+      seen: self.seen,
+    }
+  }
+}
+
+impl<T: FFISafe> Drop for Receiver<T> {
+  fn drop(&mut self) {
+    self.shared().inner.lock().receivers -= 1;
+
+    unsafe { Shared::release(self.shared) };
+  }
+}
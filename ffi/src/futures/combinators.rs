@@ -0,0 +1,141 @@
+//! Combinators building a single [`FutureTask`] out of others, on the plugin side
+//!
+//! The children are polled by the composite task, so the host only crosses the
+//! boundary once per poll no matter how many of them there are. A child that panicked
+//! makes the composite task panic with the same message.
+
+use std::{
+  future::{Future, poll_fn},
+  pin::Pin,
+  task::Poll,
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::{FFIFuture, FutureError, FutureTask, implements::create_fallible_future},
+  vector::Vector,
+};
+
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+/// The output of [`FutureTask::select`], telling which task completed first
+pub enum Either<A, B> {
+  Left(A),
+  Right(B),
+}
+
+unsafe impl<A: FFISafe, B: FFISafe> FFISafe for Either<A, B> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+type Child<T, E> = Pin<Box<FFIFuture<T, E>>>;
+
+fn child<T: FFISafe, E: FFISafe>(task: FutureTask<T, E>) -> Child<T, E> {
+  Box::pin(FFIFuture::new(task))
+}
+
+impl<T: FFISafe + Send, E: FFISafe + Send> FutureTask<T, E> {
+  /// Completes with the outputs of every task, in order
+  ///
+  /// Fails as soon as any of them fails, the others are aborted.
+  pub fn join_all<I: IntoIterator<Item = Self>>(tasks: I) -> FutureTask<Vector<T>, E> {
+    let mut children = tasks
+      .into_iter()
+      .map(|task| Some(child(task)))
+      .collect::<Vec<_>>();
+
+    create_fallible_future(async move {
+      let mut outputs = children.iter().map(|_| None).collect::<Vec<_>>();
+
+      poll_fn(|cx| {
+        let mut pending = false;
+
+        for (slot, output) in children.iter_mut().zip(outputs.iter_mut()) {
+          // Completed children must not be polled again
+          let Some(fut) = slot else {
+            continue;
+          };
+
+          match fut.as_mut().poll(cx) {
+            Poll::Pending => pending = true,
+            Poll::Ready(Ok(out)) => {
+              *output = Some(out);
+              *slot = None;
+            }
+            Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+          }
+        }
+
+        if pending {
+          Poll::Pending
+        } else {
+          Poll::Ready(Ok(()))
+        }
+      })
+      .await
+      .map_err(FutureError::into_failed)?;
+
+      let mut out = Vector::new();
+      for output in outputs {
+        out.push(output.unwrap());
+      }
+
+      Ok(out)
+    })
+  }
+
+  /// Completes with whichever of `self` & `other` completes first, the other one is
+  /// aborted
+  ///
+  /// `self` is polled first, so it wins if both are ready.
+  pub fn select<U: FFISafe + Send>(self, other: FutureTask<U, E>) -> FutureTask<Either<T, U>, E> {
+    let (mut left, mut right) = (child(self), child(other));
+
+    create_fallible_future(async move {
+      poll_fn(|cx| {
+        if let Poll::Ready(out) = left.as_mut().poll(cx) {
+          return Poll::Ready(out.map(Either::Left));
+        }
+
+        right.as_mut().poll(cx).map(|out| out.map(Either::Right))
+      })
+      .await
+      .map_err(FutureError::into_failed)
+    })
+  }
+
+  /// Completes with the first of `tasks` to complete, the others are aborted
+  ///
+  /// # Panics
+  ///
+  /// If `tasks` is empty, as it would never complete.
+  pub fn race<I: IntoIterator<Item = Self>>(tasks: I) -> Self {
+    let mut children = tasks.into_iter().map(child).collect::<Vec<_>>();
+
+    assert!(!children.is_empty(), "raced an empty list of tasks");
+
+    create_fallible_future(async move {
+      poll_fn(|cx| {
+        children
+          .iter_mut()
+          .find_map(|fut| match fut.as_mut().poll(cx) {
+            Poll::Ready(out) => Some(Poll::Ready(out)),
+            Poll::Pending => None,
+          })
+          .unwrap_or(Poll::Pending)
+      })
+      .await
+      .map_err(FutureError::into_failed)
+    })
+  }
+
+  /// Maps the output of the task with `f`, errors are passed on as they are
+  pub fn map<U: FFISafe, F: FnOnce(T) -> U + Send>(self, f: F) -> FutureTask<U, E> {
+    let fut = child(self);
+
+    create_fallible_future(async move { fut.await.map(f).map_err(FutureError::into_failed) })
+  }
+}
@@ -0,0 +1,48 @@
+//! Drives futures handed over through [`super::FutureTask::on_complete`]
+//!
+//! Every dylib has its own queue, so a plugin registers [`drive_completions`] as one
+//! of its savmasync reactors, e.g.
+//!
+//! ```ignore
+//! saffi::savmasync::generate! {
+//!   COMPLETIONS => (1, saffi::futures::completion::drive_completions)
+//! }
+//! ```
+
+use std::{collections::VecDeque, ffi::c_void, mem, sync::Mutex};
+
+/// A future woken in completion mode
+pub(crate) struct Ready {
+  pub(crate) state: *mut c_void,
+  /// Polls the future, releasing the reference taken when it was queued
+  pub(crate) drive: unsafe fn(*mut c_void),
+}
+
+unsafe impl Send for Ready {}
+
+static QUEUE: Mutex<VecDeque<Ready>> = Mutex::new(VecDeque::new());
+
+pub(crate) fn schedule(ready: Ready) {
+  QUEUE
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .push_back(ready);
+}
+
+/// Polls every future woken since the last call, returns whether there was any
+///
+/// This has the signature of a savmasync reactor. Futures woken while this runs are
+/// polled by the next call.
+pub extern "C" fn drive_completions() -> bool {
+  let ready = mem::take(&mut *QUEUE.lock().unwrap_or_else(|e| e.into_inner()));
+
+  if ready.is_empty() {
+    return false;
+  }
+
+  for task in ready {
+    unsafe { (task.drive)(task.state) };
+  }
+
+  true
+}
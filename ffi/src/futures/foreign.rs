@@ -0,0 +1,228 @@
+//! Lets foreign executors, like a C event loop, poll a [`FutureTask`] with their own
+//! [`WakerVTable`]
+//!
+//! The plugin turns the task into a [`CFuture`] with [`FutureTask::into_c_future`],
+//! which hides the output type, then the executor polls it through
+//! [`saffi_future_poll`] & drops it through [`saffi_future_drop`].
+
+use std::{
+  ffi::c_void,
+  mem::{ManuallyDrop, align_of, size_of},
+  ptr,
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::RTBox,
+  futures::{CBReason, CWaker, FutureTask, MaybeData, WakerVTable},
+  string::str::SharableStr,
+};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFuturePoll {
+  /// The waker is called once it can make progress
+  Pending = 0,
+  /// The output `T` was written to `out`
+  Ready = 1,
+  /// The error `E` was written to `out`
+  Failed = 2,
+  /// The panic message was written to `out`, as a `SharableStr`
+  Panicked = 3,
+  /// The future was polled after it completed, with a different waker vtable than
+  /// the first poll, or the plugin misbehaved. Nothing was written to `out`
+  ProtocolViolation = 4,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The size & alignment the `out` buffer given to [`saffi_future_poll`] needs
+pub struct CFutureLayout {
+  pub size: usize,
+  pub align: usize,
+}
+
+type PollFn =
+  unsafe extern "C" fn(*mut CFuture, CWaker, *const WakerVTable, *mut c_void) -> CFuturePoll;
+
+#[repr(C)]
+/// A [`FutureTask`] of any output type, always behind a pointer
+pub struct CFuture {
+  poll: PollFn,
+  layout: CFutureLayout,
+}
+
+#[repr(C)]
+struct Erased<T: FFISafe, E: FFISafe> {
+  header: CFuture,
+
+  /// Aborted or cleaned up by our own drop
+  task: ManuallyDrop<FutureTask<T, E>>,
+  /// The vtable the waker was sealed with, null before the first poll
+  sealed: *const WakerVTable,
+  /// Data of the last waker sent
+  last_data: *const c_void,
+  done: bool,
+}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for Erased<T, E> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Erased<T, E> {
+  unsafe extern "C" fn poll(
+    this: *mut CFuture,
+    waker: CWaker,
+    vtable: *const WakerVTable,
+    out: *mut c_void,
+  ) -> CFuturePoll {
+    let this = unsafe { &mut *(this as *mut Self) };
+    let (state, cb) = (this.task._state, this.task._cb);
+
+    if this.done {
+      return CFuturePoll::ProtocolViolation;
+    }
+
+    if this.sealed.is_null() {
+      cb(state, CBReason::SealWakerVTable { vtable });
+      this.sealed = vtable;
+    } else if !ptr::eq(this.sealed, vtable) {
+      // The plugin copied the first vtable, it would call the wrong functions
+      return CFuturePoll::ProtocolViolation;
+    }
+
+    if this.last_data.is_null() || !ptr::eq(this.last_data, waker.data()) {
+      this.last_data = waker.data();
+
+      // The waker is only borrowed for this call, the plugin gets its own
+      let waker = unsafe { ((*vtable).waker_clone)(waker) };
+      cb(state, CBReason::Waker { waker });
+    }
+
+    let output = cb(state, CBReason::PollCollect);
+
+    if output.is_collected() {
+      this.done = true;
+
+      return CFuturePoll::ProtocolViolation;
+    }
+
+    let status = unsafe {
+      match output.into_data() {
+        MaybeData::None => return CFuturePoll::Pending,
+        MaybeData::Some(x) => {
+          (out as *mut T).write(x);
+          CFuturePoll::Ready
+        }
+        MaybeData::Err(e) => {
+          (out as *mut E).write(e);
+          CFuturePoll::Failed
+        }
+        MaybeData::Panicked(message) => {
+          (out as *mut SharableStr).write(message);
+          CFuturePoll::Panicked
+        }
+      }
+    };
+
+    this.done = true;
+
+    status
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Drop for Erased<T, E> {
+  fn drop(&mut self) {
+    let reason = if self.done {
+      CBReason::Cleanup
+    } else {
+      CBReason::Abort
+    };
+
+    (self.task._cb)(self.task._state, reason);
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> FutureTask<T, E> {
+  /// Hides the output type, so any executor can poll the task through
+  /// [`saffi_future_poll`]
+  ///
+  /// The result must be dropped through [`saffi_future_drop`].
+  pub fn into_c_future(self) -> *mut CFuture {
+    let erased = Erased {
+      header: CFuture {
+        poll: Erased::<T, E>::poll,
+        layout: CFutureLayout {
+          size: size_of::<T>()
+            .max(size_of::<E>())
+            .max(size_of::<SharableStr>()),
+          align: align_of::<T>()
+            .max(align_of::<E>())
+            .max(align_of::<SharableStr>()),
+        },
+      },
+      task: ManuallyDrop::new(self),
+      sealed: ptr::null(),
+      last_data: ptr::null(),
+      done: false,
+    };
+
+    match RTBox::new(erased) {
+      Some(erased) => erased.into_raw() as _,
+      None => panic!("Allocation Failed"),
+    }
+  }
+}
+
+#[unsafe(no_mangle)]
+/// Returns what `out` of [`saffi_future_poll`] must be able to hold
+///
+/// # Safety
+///
+/// `task` must come from [`FutureTask::into_c_future`] & not be dropped yet
+pub unsafe extern "C" fn saffi_future_output_layout(task: *const CFuture) -> CFutureLayout {
+  unsafe { (*task).layout }
+}
+
+#[unsafe(no_mangle)]
+/// Polls the task, see [`CFuturePoll`] for what is written to `out`
+///
+/// `waker_data` is borrowed for this call, the plugin keeps its own copy made through
+/// `waker_vtable->waker_clone`. Every poll of the same task must use the same
+/// `waker_vtable`, which must stay valid until the task is dropped. The `vtable` part
+/// of the wakers handed to it is `waker_vtable` itself.
+///
+/// # Safety
+///
+/// `task` must come from [`FutureTask::into_c_future`] & not be dropped yet, `out`
+/// must be valid for writes of [`saffi_future_output_layout`] & the functions of
+/// `waker_vtable` must be safe to call with `waker_data` from any thread.
+pub unsafe extern "C" fn saffi_future_poll(
+  task: *mut CFuture,
+  waker_data: *const c_void,
+  waker_vtable: *const WakerVTable,
+  out: *mut c_void,
+) -> CFuturePoll {
+  unsafe {
+    ((*task).poll)(
+      task,
+      CWaker::new(waker_data, waker_vtable as _),
+      waker_vtable,
+      out,
+    )
+  }
+}
+
+#[unsafe(no_mangle)]
+/// Drops the task, aborting the future if it did not complete
+///
+/// # Safety
+///
+/// `task` must come from [`FutureTask::into_c_future`] & not be dropped yet
+pub unsafe extern "C" fn saffi_future_drop(task: *mut CFuture) {
+  // The vtable of the box knows the real type
+  unsafe { drop(RTBox::<c_void>::from_thin_raw(task as _)) };
+}
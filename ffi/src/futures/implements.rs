@@ -1,18 +1,33 @@
 use std::{
+  any::Any,
+  convert::Infallible,
   ffi::c_void,
   marker::PhantomPinned,
   mem::offset_of,
+  panic::{AssertUnwindSafe, catch_unwind},
   pin::Pin,
+  sync::atomic::{AtomicU8, Ordering},
   task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
 };
 
+use futures_core::Stream;
+
 use crate::{
   FFISafe,
-  futures::{CBReason, FFIFuture, FutureTask, MaybeData, Result, atomiccw::AtomicFFICWaker},
+  futures::{
+    CBReason, CompletionFn, FFIFuture, FutureTask, Result, WakerVTable,
+    atomiccw::AtomicFFICWaker,
+    completion::{self, Ready},
+    local::{LocalFfiStream, LocalFutureTask},
+    pool,
+    stream::{FfiStream, StreamItem, StreamReason},
+  },
+  string::str::SharableStr,
 };
 
 #[repr(C, align(64))]
-struct FutureState<F: Future> {
+/// Shared by futures & streams, `F` is either of them
+struct FutureState<F> {
   // Our high-speed, lock-free waker state (Inlined!)
   waker_atomic: AtomicFFICWaker,
 
@@ -22,12 +37,23 @@ struct FutureState<F: Future> {
   // The actual task
   future: Option<F>,
 
+  // Set in completion mode, see `CBReason::OnComplete`
+  completion: Option<(*const c_void, *mut c_void)>,
+  // IDLE, QUEUED, RUNNING or NOTIFIED, only used in completion mode
+  schedule: AtomicU8,
+
   _pin: PhantomPinned,
 }
 
+const IDLE: u8 = 0;
+const QUEUED: u8 = 1;
+const RUNNING: u8 = 2;
+/// Woken while running, it is queued again once the poll is over
+const NOTIFIED: u8 = 3;
+
 const _SAFETY_2: () = assert!(offset_of!(FutureState<FFIFuture<u8>>, waker_atomic) == 0);
 
-impl<F: Future> FutureState<F> {
+impl<F> FutureState<F> {
   const VTABLE: RawWakerVTable = RawWakerVTable::new(
     Self::clone_waker,
     Self::wake_consume,
@@ -51,7 +77,7 @@ impl<F: Future> FutureState<F> {
     state.wake();
 
     if state.dec() {
-      drop(unsafe { Box::from_raw(ptr as *mut FutureState<F>) });
+      unsafe { Self::free(ptr as *mut Self) };
     }
   }
 
@@ -64,85 +90,399 @@ impl<F: Future> FutureState<F> {
     let state = unsafe { &*(ptr as *const AtomicFFICWaker) };
 
     if state.dec() {
-      drop(unsafe { Box::from_raw(ptr as *mut FutureState<F>) });
+      unsafe { Self::free(ptr as *mut Self) };
+    }
+  }
+
+  fn boxed(future: F) -> *mut c_void {
+    let ptr = pool::alloc(size_of::<Self>(), align_of::<Self>()) as *mut Self;
+
+    unsafe {
+      ptr.write(FutureState {
+        future: Some(future),
+        completion: None,
+        schedule: AtomicU8::new(IDLE),
+        raw_waker: None,
+        waker_atomic: AtomicFFICWaker::new(),
+        _pin: PhantomPinned,
+      })
+    };
+
+    ptr as _
+  }
+
+  /// Drops & frees a state made by [`FutureState::boxed`] once the last ref is gone
+  unsafe fn free(ptr: *mut Self) {
+    unsafe {
+      ptr.drop_in_place();
+      pool::free(ptr as _, size_of::<Self>(), align_of::<Self>());
+    }
+  }
+
+  fn seal(&mut self, vtable: *const WakerVTable) {
+    self.waker_atomic.set_vtable(vtable);
+    self.waker_atomic.inc();
+
+    // Construct the Waker once.
+    let w = unsafe {
+      Waker::from_raw(RawWaker::new(
+        &self.waker_atomic as *const _ as *const (),
+        &Self::VTABLE,
+      ))
+    };
+    self.raw_waker = Some(w);
+  }
+
+  /// Drops the future & our reference to the state, see [`CBReason::Abort`]
+  unsafe fn release(&mut self) {
+    // The drop is auto handled by rust Waker's drop
+    _ = self.raw_waker.take();
+
+    // Nobody is left to report a panic in the drop of the future to
+    self.drop_future();
+
+    if self.waker_atomic.dec() {
+      unsafe { Self::free(self) };
+    }
+  }
+
+  fn drop_future(&mut self) {
+    // Dropped in place, the future is pinned & may be linked into e.g. a timer wheel
+    if let Err(payload) = catch_unwind(AssertUnwindSafe(|| self.future = None)) {
+      discard_panic(payload);
+    }
+  }
+
+  /// Polls the future through `poll`, dropping it if it panics
+  fn poll_with<R>(
+    &mut self,
+    poll: impl FnOnce(Pin<&mut F>, &mut Context<'_>) -> R,
+  ) -> core::result::Result<R, SharableStr> {
+    let waker = unsafe { self.raw_waker.as_ref().unwrap_unchecked() };
+    let mut ctx = Context::from_waker(waker);
+
+    debug_assert!(self.future.is_some());
+
+    let fut = unsafe { Pin::new_unchecked(self.future.as_mut().unwrap_unchecked()) };
+
+    match catch_unwind(AssertUnwindSafe(|| poll(fut, &mut ctx))) {
+      Ok(out) => Ok(out),
+      Err(payload) => {
+        let message = SharableStr::create(panic_message(&*payload));
+        discard_panic(payload);
+
+        // The future may be broken, so it is never touched again
+        self.drop_future();
+
+        Err(message)
+      }
     }
   }
 }
 
-pub fn create_future<F: Future>(fut: F) -> FutureTask<F::Output>
+/// Completion mode, the plugin wakes & polls the future itself through
+/// [`completion::drive_completions`]
+impl<F, T, E> FutureState<F>
+where
+  F: Future<Output = core::result::Result<T, E>>,
+  T: FFISafe,
+  E: FFISafe,
+{
+  const COMPLETION_VTABLE: RawWakerVTable = RawWakerVTable::new(
+    Self::clone_completion,
+    Self::wake_completion,
+    Self::wake_completion_by_ref,
+    Self::drop_waker,
+  );
+
+  fn start(&mut self, callback: *const c_void, ctx: *mut c_void) {
+    self.completion = Some((callback, ctx));
+
+    // Owned by `raw_waker`
+    self.waker_atomic.inc();
+    self.raw_waker = Some(unsafe {
+      Waker::from_raw(RawWaker::new(
+        self as *const _ as *const (),
+        &Self::COMPLETION_VTABLE,
+      ))
+    });
+
+    unsafe { Self::wake_completion_by_ref(self as *const _ as *const ()) };
+  }
+
+  unsafe fn clone_completion(ptr: *const ()) -> RawWaker {
+    unsafe { (&*(ptr as *const AtomicFFICWaker)).inc() };
+    RawWaker::new(ptr, &Self::COMPLETION_VTABLE)
+  }
+
+  unsafe fn wake_completion(ptr: *const ()) {
+    unsafe {
+      Self::wake_completion_by_ref(ptr);
+      Self::drop_waker(ptr);
+    }
+  }
+
+  unsafe fn wake_completion_by_ref(ptr: *const ()) {
+    let state = unsafe { &*(ptr as *const Self) };
+
+    let mut current = state.schedule.load(Ordering::Acquire);
+
+    loop {
+      let next = match current {
+        IDLE => QUEUED,
+        RUNNING => NOTIFIED,
+        _ => return,
+      };
+
+      match state
+        .schedule
+        .compare_exchange_weak(current, next, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => break,
+        Err(actual) => current = actual,
+      }
+    }
+
+    if current == IDLE {
+      // Owned by the queue
+      state.waker_atomic.inc();
+
+      completion::schedule(Ready {
+        state: ptr as _,
+        drive: Self::drive,
+      });
+    }
+  }
+
+  unsafe fn drive(ptr: *mut c_void) {
+    let state = unsafe { &mut *(ptr as *mut Self) };
+    state.schedule.store(RUNNING, Ordering::Release);
+
+    if state.future.is_some() {
+      let out = match state.poll_with(|fut, ctx| fut.poll(ctx)) {
+        Ok(Poll::Ready(Ok(x))) => Some(Result::ready(x)),
+        Ok(Poll::Ready(Err(e))) => Some(Result::failed(e)),
+        Ok(Poll::Pending) => None,
+        Err(message) => Some(Result::panicked(message)),
+      };
+
+      if let Some(out) = out {
+        let (callback, ctx) = unsafe { state.completion.take().unwrap_unchecked() };
+        let callback: CompletionFn<T, E> = unsafe { core::mem::transmute(callback) };
+
+        // Drops the future & the reference of the host, the queue still holds one
+        unsafe { state.release() };
+
+        callback(ctx, out);
+      }
+    }
+
+    if state
+      .schedule
+      .compare_exchange(RUNNING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+      .is_err()
+    {
+      // Woken while running, the reference of the queue moves on to the next run
+      state.schedule.store(QUEUED, Ordering::Release);
+
+      completion::schedule(Ready {
+        state: ptr,
+        drive: Self::drive,
+      });
+
+      return;
+    }
+
+    if state.waker_atomic.dec() {
+      unsafe { Self::free(ptr as *mut Self) };
+    }
+  }
+}
+
+pub fn create_future<F: Future + Send>(fut: F) -> FutureTask<F::Output>
 where
   F::Output: FFISafe,
+{
+  create_fallible_future(async move { Ok::<_, Infallible>(fut.await) })
+}
+
+/// Same as [`create_future`], but an `Err` output fails the task with it
+pub fn create_fallible_future<F, T, E>(fut: F) -> FutureTask<T, E>
+where
+  F: Future<Output = core::result::Result<T, E>> + Send,
+  T: FFISafe,
+  E: FFISafe,
 {
   FutureTask {
-    _state: Box::into_raw(Box::new(FutureState {
-      future: Some(fut),
-      raw_waker: None,
-      waker_atomic: AtomicFFICWaker::new(),
-      _pin: PhantomPinned,
-    })) as _,
-    _cb: poll_future::<F>,
+    _state: FutureState::boxed(fut),
+    _cb: poll_future::<F, T, E>,
   }
 }
 
-extern "C" fn poll_future<F: Future>(state_ptr: *mut c_void, action: CBReason) -> Result<F::Output>
+/// Same as [`create_future`], for futures which are not `Send`
+pub fn create_local_future<F: Future>(fut: F) -> LocalFutureTask<F::Output>
 where
   F::Output: FFISafe,
+{
+  create_local_fallible_future(async move { Ok::<_, Infallible>(fut.await) })
+}
+
+/// Same as [`create_fallible_future`], for futures which are not `Send`
+pub fn create_local_fallible_future<F, T, E>(fut: F) -> LocalFutureTask<T, E>
+where
+  F: Future<Output = core::result::Result<T, E>>,
+  T: FFISafe,
+  E: FFISafe,
+{
+  LocalFutureTask::new(FutureTask {
+    _state: FutureState::boxed(fut),
+    _cb: poll_future::<F, T, E>,
+  })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message
+  } else {
+    "Box<dyn Any>"
+  }
+}
+
+/// Drops the payload of a panic that cannot be delivered to anyone
+fn discard_panic(payload: Box<dyn Any + Send>) {
+  // Even dropping the payload may panic
+  _ = catch_unwind(AssertUnwindSafe(move || drop(payload)));
+}
+
+extern "C" fn poll_future<F, T, E>(state_ptr: *mut c_void, action: CBReason) -> Result<T, E>
+where
+  F: Future<Output = core::result::Result<T, E>>,
+  T: FFISafe,
+  E: FFISafe,
 {
   let state = unsafe { &mut *(state_ptr as *mut FutureState<F>) };
 
   match action {
-    CBReason::SealWakerVTable { vtable } => {
-      state.waker_atomic.set_vtable(vtable);
-      state.waker_atomic.inc();
+    CBReason::SealWakerVTable { vtable } => state.seal(vtable),
+    CBReason::Waker { waker } => state.waker_atomic.update(waker),
+    CBReason::Cleanup | CBReason::Abort => unsafe { state.release() },
+    CBReason::OnComplete { callback, ctx } => state.start(callback, ctx),
+    CBReason::PollCollect => {
+      // Fused, the future has been dropped as soon as it completed or panicked
+      if state.future.is_none() {
+        return Result::collected();
+      }
 
-      // Construct the Waker once.
-      let w = unsafe {
-        Waker::from_raw(RawWaker::new(
-          &state.waker_atomic as *const _ as *const (),
-          &FutureState::<F>::VTABLE,
-        ))
+      let out = match state.poll_with(|fut, ctx| fut.poll(ctx)) {
+        Ok(Poll::Ready(Ok(x))) => Result::ready(x),
+        Ok(Poll::Ready(Err(e))) => Result::failed(e),
+        Ok(Poll::Pending) => return Result::pending(),
+        Err(message) => return Result::panicked(message),
       };
-      state.raw_waker = Some(w);
-    }
-    CBReason::Waker { waker } => {
-      state.waker_atomic.update(waker);
+
+      state.drop_future();
+
+      return out;
     }
-    CBReason::Cleanup | CBReason::Abort => unsafe {
-      // The drop is auto handled by rust Waker's drop
-      _ = state.raw_waker.take();
-      _ = state.future.take();
+  }
 
-      if state.waker_atomic.dec() {
-        drop(Box::from_raw(state as *mut FutureState<F>));
-      }
-    },
-    CBReason::PollCollect => {
-      let waker = unsafe { state.raw_waker.as_ref().unwrap_unchecked() };
-      let mut ctx = Context::from_waker(waker);
+  Result::pending()
+}
 
-      debug_assert!(state.future.is_some());
+/// Exposes a stream as a [`FfiStream`], see [`create_future`]
+pub fn create_stream<S: Stream + Send>(stream: S) -> FfiStream<S::Item>
+where
+  S::Item: FFISafe,
+{
+  create_fallible_stream(Infallibly(stream))
+}
 
-      let fut = unsafe { Pin::new_unchecked(state.future.as_mut().unwrap_unchecked()) };
+/// Maps every item of the stream to `Ok`
+struct Infallibly<S>(S);
 
-      match fut.poll(&mut ctx) {
-        Poll::Ready(x) => {
-          return Result {
-            flag: 0,
-            output: MaybeData::Some(x),
-          };
-        }
-        Poll::Pending => {
-          return Result {
-            flag: 0,
-            output: MaybeData::None,
-          };
+impl<S: Stream> Stream for Infallibly<S> {
+  type Item = core::result::Result<S::Item, Infallible>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let stream = unsafe { self.map_unchecked_mut(|this| &mut this.0) };
+
+    stream.poll_next(cx).map(|item| item.map(Ok))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.0.size_hint()
+  }
+}
+
+/// Same as [`create_stream`], but every `Err` item is sent as an error item
+///
+/// Unlike a future, the stream goes on after an error.
+pub fn create_fallible_stream<S, T, E>(stream: S) -> FfiStream<T, E>
+where
+  S: Stream<Item = core::result::Result<T, E>> + Send,
+  T: FFISafe,
+  E: FFISafe,
+{
+  FfiStream {
+    _state: FutureState::boxed(stream),
+    _cb: poll_stream::<S, T, E>,
+  }
+}
+
+/// Same as [`create_stream`], for streams which are not `Send`
+pub fn create_local_stream<S: Stream>(stream: S) -> LocalFfiStream<S::Item>
+where
+  S::Item: FFISafe,
+{
+  create_local_fallible_stream(Infallibly(stream))
+}
+
+/// Same as [`create_fallible_stream`], for streams which are not `Send`
+pub fn create_local_fallible_stream<S, T, E>(stream: S) -> LocalFfiStream<T, E>
+where
+  S: Stream<Item = core::result::Result<T, E>>,
+  T: FFISafe,
+  E: FFISafe,
+{
+  LocalFfiStream::new(FfiStream {
+    _state: FutureState::boxed(stream),
+    _cb: poll_stream::<S, T, E>,
+  })
+}
+
+extern "C" fn poll_stream<S, T, E>(state_ptr: *mut c_void, action: StreamReason) -> StreamItem<T, E>
+where
+  S: Stream<Item = core::result::Result<T, E>>,
+  T: FFISafe,
+  E: FFISafe,
+{
+  let state = unsafe { &mut *(state_ptr as *mut FutureState<S>) };
+
+  match action {
+    StreamReason::SealWakerVTable { vtable } => state.seal(vtable),
+    StreamReason::Waker { waker } => state.waker_atomic.update(waker),
+    StreamReason::Cleanup | StreamReason::Abort => unsafe { state.release() },
+    StreamReason::PollNext => {
+      // Fused, the stream has been dropped as soon as it finished
+      if state.future.is_none() {
+        return StreamItem::Finished;
+      }
+
+      match state.poll_with(|stream, ctx| stream.poll_next(ctx)) {
+        Ok(Poll::Ready(Some(Ok(x)))) => return StreamItem::Ready(x),
+        Ok(Poll::Ready(Some(Err(e)))) => return StreamItem::Err(e),
+        Ok(Poll::Ready(None)) => {
+          state.drop_future();
+
+          return StreamItem::Finished;
         }
+        Ok(Poll::Pending) => return StreamItem::Pending,
+        Err(message) => return StreamItem::Panicked(message),
       }
     }
   }
 
-  Result {
-    flag: 0,
-    output: MaybeData::None,
-  }
+  StreamItem::Pending
 }
@@ -0,0 +1,219 @@
+//! `!Send` counterparts of [`FutureTask`], [`FfiStream`] & their awaiting sides
+//!
+//! A [`LocalFutureTask`] or [`LocalFfiStream`] must be polled & dropped on the thread
+//! which created it, so it can carry futures holding `Rc`s & friends. A local task
+//! cannot be handed to
+//! [`completion::drive_completions`](super::completion::drive_completions), which may
+//! run on any thread.
+
+use std::{
+  convert::Infallible,
+  future::Future,
+  marker::PhantomData,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::{
+    FFIFuture, FutureCallback, FutureError, FutureTask, State,
+    foreign::CFuture,
+    stream::{FFIStream, FfiStream, StreamCallback},
+  },
+};
+
+#[repr(transparent)]
+/// A [`FutureTask`] whose future is not `Send`
+///
+/// Made by [`create_local_future`](super::implements::create_local_future), any
+/// [`FutureTask`] can be turned into one.
+///
+/// ```compile_fail
+/// use saffi::futures::implements::create_local_future;
+///
+/// fn send<T: Send>(_: T) {}
+/// send(create_local_future(async { 1u8 }));
+/// ```
+pub struct LocalFutureTask<T: FFISafe, E: FFISafe = Infallible> {
+  task: FutureTask<T, E>,
+  _not_send: PhantomData<*const ()>,
+}
+
+impl<T: FFISafe, E: FFISafe> LocalFutureTask<T, E> {
+  pub(crate) const fn new(task: FutureTask<T, E>) -> Self {
+    Self {
+      task,
+      _not_send: PhantomData,
+    }
+  }
+
+  /// See [`FutureTask::into_raw`]
+  pub fn into_raw(self) -> (State, FutureCallback<T, E>) {
+    self.task.into_raw()
+  }
+
+  /// See [`FutureTask::from_raw`]
+  ///
+  /// # Safety
+  ///
+  /// The parts must come from `into_raw` of a `LocalFutureTask<T, E>` or a
+  /// `FutureTask<T, E>` built with the same saffi version, nobody else may own them &
+  /// they must only be used on this thread.
+  pub unsafe fn from_raw(state: State, cb: FutureCallback<T, E>) -> Option<Self> {
+    unsafe { FutureTask::from_raw(state, cb) }.map(Self::new)
+  }
+
+  /// See [`FutureTask::leak`]
+  pub fn leak(self) -> State {
+    self.task.leak()
+  }
+
+  #[inline(always)]
+  /// See [`FutureTask::as_raw`]
+  pub const fn as_raw(&self) -> State {
+    self.task.as_raw()
+  }
+
+  /// See [`FutureTask::with_raw`]
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(State, FutureCallback<T, E>) -> R) -> R {
+    self.task.with_raw(f)
+  }
+
+  /// See [`FutureTask::into_c_future`], the foreign executor must poll & drop it on
+  /// this thread
+  pub fn into_c_future(self) -> *mut CFuture {
+    self.task.into_c_future()
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> From<FutureTask<T, E>> for LocalFutureTask<T, E> {
+  fn from(task: FutureTask<T, E>) -> Self {
+    Self::new(task)
+  }
+}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for LocalFutureTask<T, E> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+#[repr(transparent)]
+/// Awaits a [`LocalFutureTask`], see [`FFIFuture`]
+pub struct LocalFFIFuture<T: FFISafe, E: FFISafe = Infallible> {
+  inner: FFIFuture<T, E>,
+  _not_send: PhantomData<*const ()>,
+}
+
+impl<T: FFISafe, E: FFISafe> LocalFFIFuture<T, E> {
+  pub fn new(task: LocalFutureTask<T, E>) -> Self {
+    Self {
+      inner: FFIFuture::new(task.task),
+      _not_send: PhantomData,
+    }
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Future for LocalFFIFuture<T, E> {
+  type Output = core::result::Result<T, FutureError<E>>;
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    unsafe { self.map_unchecked_mut(|this| &mut this.inner) }.poll(cx)
+  }
+}
+
+#[repr(transparent)]
+/// A [`FfiStream`] whose stream is not `Send`
+///
+/// Made by [`create_local_stream`](super::implements::create_local_stream), any
+/// [`FfiStream`] can be turned into one.
+pub struct LocalFfiStream<T: FFISafe, E: FFISafe = Infallible> {
+  stream: FfiStream<T, E>,
+  _not_send: PhantomData<*const ()>,
+}
+
+impl<T: FFISafe, E: FFISafe> LocalFfiStream<T, E> {
+  pub(crate) const fn new(stream: FfiStream<T, E>) -> Self {
+    Self {
+      stream,
+      _not_send: PhantomData,
+    }
+  }
+
+  /// See [`FfiStream::into_raw`]
+  pub fn into_raw(self) -> (State, StreamCallback<T, E>) {
+    self.stream.into_raw()
+  }
+
+  /// See [`FfiStream::from_raw`]
+  ///
+  /// # Safety
+  ///
+  /// The parts must come from `into_raw` of a `LocalFfiStream<T, E>` or a
+  /// `FfiStream<T, E>` built with the same saffi version, nobody else may own them &
+  /// they must only be used on this thread.
+  pub unsafe fn from_raw(state: State, cb: StreamCallback<T, E>) -> Option<Self> {
+    unsafe { FfiStream::from_raw(state, cb) }.map(Self::new)
+  }
+
+  /// See [`FfiStream::leak`]
+  pub fn leak(self) -> State {
+    self.stream.leak()
+  }
+
+  #[inline(always)]
+  /// See [`FfiStream::as_raw`]
+  pub const fn as_raw(&self) -> State {
+    self.stream.as_raw()
+  }
+
+  /// See [`FfiStream::with_raw`]
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(State, StreamCallback<T, E>) -> R) -> R {
+    self.stream.with_raw(f)
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> From<FfiStream<T, E>> for LocalFfiStream<T, E> {
+  fn from(stream: FfiStream<T, E>) -> Self {
+    Self::new(stream)
+  }
+}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for LocalFfiStream<T, E> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+/// Consumes a [`LocalFfiStream`], see [`FFIStream`]
+pub struct LocalFFIStream<T: FFISafe, E: FFISafe = Infallible> {
+  inner: FFIStream<T, E>,
+  _not_send: PhantomData<*const ()>,
+}
+
+impl<T: FFISafe, E: FFISafe> LocalFFIStream<T, E> {
+  pub fn new(stream: LocalFfiStream<T, E>) -> Self {
+    Self {
+      inner: FFIStream::new(stream.stream),
+      _not_send: PhantomData,
+    }
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Stream for LocalFFIStream<T, E> {
+  type Item = core::result::Result<T, FutureError<E>>;
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    unsafe { self.map_unchecked_mut(|this| &mut this.inner) }.poll_next(cx)
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> FusedStream for LocalFFIStream<T, E> {
+  fn is_terminated(&self) -> bool {
+    self.inner.is_terminated()
+  }
+}
@@ -1,8 +1,9 @@
 use std::{
   cell::UnsafeCell,
+  convert::Infallible,
   hint::cold_path,
   marker::PhantomPinned,
-  mem::ManuallyDrop,
+  mem::{ManuallyDrop, forget},
   ops::BitAnd,
   os::raw::c_void,
   ptr::addr_of,
@@ -13,10 +14,19 @@ use std::{
 use crate::{
   FFISafe,
   I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  string::str::SharableStr,
 };
 
 pub mod atomiccw;
+pub mod channel;
+pub mod combinators;
+pub mod completion;
+pub mod foreign;
 pub mod implements;
+pub mod local;
+mod pool;
+pub mod promise;
+pub mod stream;
 
 pub type State = *mut c_void;
 
@@ -41,6 +51,15 @@ pub enum CBReason {
 
   Abort,
   Cleanup,
+
+  /// Hands the future over to the runtime of the plugin, which drives it to completion
+  /// & then calls `callback` (a [`CompletionFn`]) with `ctx` & the output
+  ///
+  /// This is sent instead of `SealWakerVTable` and no other reason is ever sent afterwards.
+  OnComplete {
+    callback: *const c_void,
+    ctx: *mut c_void,
+  },
 }
 
 #[repr(C)]
@@ -58,6 +77,25 @@ pub struct CWaker {
   vtable: *const c_void,
 }
 
+impl CWaker {
+  /// Both parts are opaque to saffi, they are only ever handed back to the
+  /// [`WakerVTable`] the waker was sealed with
+  pub const fn new(data: *const c_void, vtable: *const c_void) -> Self {
+    Self {
+      data: data as _,
+      vtable,
+    }
+  }
+
+  pub const fn data(&self) -> *const c_void {
+    self.data as _
+  }
+
+  pub const fn vtable(&self) -> *const c_void {
+    self.vtable
+  }
+}
+
 extern "C" fn call_no_drop(waker: CWaker) {
   unsafe {
     let waker = ManuallyDrop::new(Waker::new(
@@ -102,13 +140,19 @@ extern "C" fn clone_waker(waker: CWaker) -> CWaker {
 }
 
 #[repr(C)]
-pub enum MaybeData<T> {
+pub enum MaybeData<T, E> {
   None,
   Some(T),
+  /// The future completed, but failed
+  Err(E),
+  /// Polling the future panicked, this is the panic message
+  ///
+  /// The future has been dropped & will never be polled again
+  Panicked(SharableStr),
 }
 
 #[repr(C)]
-pub struct Result<T: FFISafe> {
+pub struct Result<T: FFISafe, E: FFISafe = Infallible> {
   flag: u8,
 
   /// Case A:
@@ -121,20 +165,165 @@ pub struct Result<T: FFISafe> {
   ///
   /// For CaseB, a new waker is sent via channel
   /// shortly
-  output: MaybeData<T>,
+  ///
+  /// A flag other than 0 is a protocol violation, NOT an error of the future,
+  /// those are sent as `MaybeData::Err`
+  output: MaybeData<T, E>,
 }
 
+impl<T: FFISafe, E: FFISafe> Result<T, E> {
+  pub const fn pending() -> Self {
+    Self {
+      flag: 0,
+      output: MaybeData::None,
+    }
+  }
+
+  pub const fn ready(data: T) -> Self {
+    Self {
+      flag: 0,
+      output: MaybeData::Some(data),
+    }
+  }
+
+  pub const fn failed(err: E) -> Self {
+    Self {
+      flag: 0,
+      output: MaybeData::Err(err),
+    }
+  }
+
+  pub const fn panicked(message: SharableStr) -> Self {
+    Self {
+      flag: 0,
+      output: MaybeData::Panicked(message),
+    }
+  }
+
+  /// The future had already been collected
+  pub const fn collected() -> Self {
+    Self {
+      flag: 1,
+      output: MaybeData::None,
+    }
+  }
+
+  #[inline(always)]
+  pub const fn is_collected(&self) -> bool {
+    self.flag != 0
+  }
+
+  pub fn into_data(self) -> MaybeData<T, E> {
+    self.output
+  }
+}
+
+pub type FutureCallback<T, E = Infallible> = extern "C" fn(State, CBReason) -> Result<T, E>;
+
+/// Called once with the output of a future, see [`FutureTask::on_complete`]
+///
+/// The output is never pending, nor collected.
+pub type CompletionFn<T, E = Infallible> = extern "C" fn(ctx: *mut c_void, output: Result<T, E>);
+
 #[repr(C)]
-pub struct FutureTask<T: FFISafe> {
-  pub _state: State,
+/// An owned handle to a future living in another dylib
+///
+/// Dropping it without awaiting it aborts the future, see [`CBReason::Abort`].
+///
+/// The future either outputs a `T` or fails with an `E`. It is `Send`, so it may be
+/// polled & dropped from any thread, see [`local::LocalFutureTask`] for futures which
+/// are not.
+pub struct FutureTask<T: FFISafe, E: FFISafe = Infallible> {
+  pub(crate) _state: State,
 
   /// This is the function you're supposed to correctly handle!
   ///
   /// Return NULL once it has been consumed & When data is not available
-  pub _cb: extern "C" fn(State, CBReason) -> Result<T>,
+  pub(crate) _cb: FutureCallback<T, E>,
+}
+
+impl<T: FFISafe, E: FFISafe> FutureTask<T, E> {
+  /// Gives up ownership, returning the state and its callback
+  ///
+  /// The task must be given back to [`FutureTask::from_raw`], possibly in another dylib,
+  /// to be aborted or awaited.
+  pub fn into_raw(self) -> (State, FutureCallback<T, E>) {
+    let out = (self._state, self._cb);
+    forget(self);
+
+    out
+  }
+
+  /// Takes ownership of the parts returned by [`FutureTask::into_raw`], `None` if the
+  /// state is null
+  ///
+  /// # Safety
+  ///
+  /// The parts must come from `into_raw` of a `FutureTask<T, E>` built with the same
+  /// saffi version, and nobody else may own them. They must not come from a
+  /// [`local::LocalFutureTask`], as the task is `Send`.
+  pub unsafe fn from_raw(state: State, cb: FutureCallback<T, E>) -> Option<Self> {
+    if state.is_null() {
+      return None;
+    }
+
+    Some(Self {
+      _state: state,
+      _cb: cb,
+    })
+  }
+
+  /// Lets the plugin drive the future to completion, without ever polling it here
+  ///
+  /// `callback` is called once with `ctx` & the output, from whichever thread drives
+  /// the future (see [`completion::drive_completions`]). The task cannot be aborted
+  /// anymore.
+  pub fn on_complete(self, callback: CompletionFn<T, E>, ctx: *mut c_void) {
+    let (state, cb) = self.into_raw();
+
+    cb(
+      state,
+      CBReason::OnComplete {
+        callback: callback as *const c_void,
+        ctx,
+      },
+    );
+  }
+
+  /// Gives up ownership without ever aborting the task, returning its state
+  ///
+  /// The future, and everything it owns, is never freed.
+  pub fn leak(self) -> State {
+    self.into_raw().0
+  }
+
+  #[inline(always)]
+  /// Returns the same state as [`FutureTask::into_raw`] without giving up ownership
+  pub const fn as_raw(&self) -> State {
+    self._state
+  }
+
+  /// Lends the parts of [`FutureTask::into_raw`] to `f`, e.g. to hand them to foreign
+  /// code for the duration of a call
+  ///
+  /// `f` may call the callback, but must neither abort nor clean the task up, nor keep
+  /// the parts after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(State, FutureCallback<T, E>) -> R) -> R {
+    f(self._state, self._cb)
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Drop for FutureTask<T, E> {
+  fn drop(&mut self) {
+    (self._cb)(self._state, CBReason::Abort);
+  }
 }
 
-unsafe impl<T: FFISafe> FFISafe for FutureTask<T> {
+// The fields are private, so a task is either made from a `Send` future or through
+// the unsafe `from_raw`
+unsafe impl<T: FFISafe + Send, E: FFISafe + Send> Send for FutureTask<T, E> {}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for FutureTask<T, E> {
   fn i_am_ffisafe() -> crate::IAmFFISafe {
     I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
   }
@@ -147,19 +336,115 @@ static WAKER_VTABLE: WakerVTable = WakerVTable {
   waker_clone: clone_waker,
 };
 
+/// Why a [`FFIFuture`] did not complete with an output
+pub enum FutureError<E> {
+  /// The future itself failed
+  Failed(E),
+  /// The foreign future panicked while being polled, with this message
+  Panicked(SharableStr),
+}
+
+impl<E> FutureError<E> {
+  /// Returns the error of the future, resuming the panic on this side if it panicked
+  pub fn into_failed(self) -> E {
+    match self {
+      Self::Failed(err) => err,
+      Self::Panicked(message) => std::panic::resume_unwind(Box::new(message.to_string())),
+    }
+  }
+
+  pub fn panic_message(&self) -> Option<&str> {
+    match self {
+      Self::Failed(_) => None,
+      Self::Panicked(message) => Some(message),
+    }
+  }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Debug for FutureError<E> {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    match self {
+      Self::Failed(err) => f.debug_tuple("Failed").field(err).finish(),
+      Self::Panicked(message) => f.debug_tuple("Panicked").field(message).finish(),
+    }
+  }
+}
+
+impl<E: PartialEq> PartialEq for FutureError<E> {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Self::Failed(a), Self::Failed(b)) => a == b,
+      (Self::Panicked(a), Self::Panicked(b)) => **a == **b,
+      _ => false,
+    }
+  }
+}
+
+#[cold]
+#[track_caller]
+/// The foreign side, or the user of a [`FFIFuture`], broke the protocol
+///
+/// This is a bug, not a failure of the future, so it is never sent as an error.
+fn protocol_violation(what: &str) -> ! {
+  panic!("[ERR] FFI ASYNC PROTOCOL VIOLATION: {what}");
+}
+
 #[repr(C, align(64))]
-pub struct FFIFuture<T: FFISafe + Sized> {
-  task: FutureTask<T>,
+/// Awaits a [`FutureTask`], resolving to `Ok` with its output or `Err` if it failed
+/// or panicked
+pub struct FFIFuture<T: FFISafe + Sized, E: FFISafe = Infallible> {
+  /// Aborted or cleaned up by our own drop
+  task: ManuallyDrop<FutureTask<T, E>>,
   flags: UnsafeCell<u8>,
 
-  last_waker_data: AtomicUsize,
-  last_waker_vtable: AtomicUsize,
+  last_waker: LastWaker,
 
   _pin: std::marker::PhantomPinned,
 }
 
-impl<T: FFISafe + Sized> FFIFuture<T> {
-  pub fn new(task: FutureTask<T>) -> Self {
+#[repr(C)]
+/// The parts of the last waker sent to the foreign side
+pub(crate) struct LastWaker {
+  data: AtomicUsize,
+  vtable: AtomicUsize,
+}
+
+impl LastWaker {
+  pub(crate) const fn new() -> Self {
+    Self {
+      data: AtomicUsize::new(0),
+      vtable: AtomicUsize::new(0),
+    }
+  }
+
+  /// Returns a clone of `waker` to send to the foreign side, if it is not the last one sent
+  pub(crate) fn changed(&self, waker: &Waker) -> Option<CWaker> {
+    // 1. Extract the raw waker parts manually.
+    // A Waker is effectively: struct { data: *const (), vtable: *const () }
+    let (data_ptr, vtable_ptr) = (waker.data(), waker.vtable() as *const _ as *const ());
+
+    // 2. The "Fingerprint" Check
+    // This is essentially what will_wake does, but without the function call.
+    if { self.data.load(Ordering::Acquire) == data_ptr.addr() } && {
+      self.vtable.load(Ordering::Acquire) == vtable_ptr.addr()
+    } {
+      return None;
+    }
+
+    self.data.store(data_ptr.addr(), Ordering::Release);
+    self.vtable.store(vtable_ptr.addr(), Ordering::Release);
+
+    let new_internal = ManuallyDrop::new(waker.clone());
+
+    Some(CWaker {
+      data: new_internal.data(),
+      vtable: new_internal.vtable() as *const _ as _,
+    })
+  }
+}
+
+impl<T: FFISafe + Sized, E: FFISafe> FFIFuture<T, E> {
+  pub fn new(task: FutureTask<T, E>) -> Self {
     (task._cb)(
       task._state,
       CBReason::SealWakerVTable {
@@ -168,71 +453,48 @@ impl<T: FFISafe + Sized> FFIFuture<T> {
     );
 
     Self {
-      last_waker_data: AtomicUsize::new(0),
-      last_waker_vtable: AtomicUsize::new(0),
+      last_waker: LastWaker::new(),
       flags: UnsafeCell::new(0),
-      task,
+      task: ManuallyDrop::new(task),
       _pin: PhantomPinned,
     }
   }
 }
 
-impl<T: FFISafe + Sized> Future for FFIFuture<T> {
-  type Output = T;
+impl<T: FFISafe + Sized, E: FFISafe> Future for FFIFuture<T, E> {
+  type Output = core::result::Result<T, FutureError<E>>;
 
   fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-    let waker = cx.waker();
-
-    // 1. Extract the raw waker parts manually.
-    // A Waker is effectively: struct { data: *const (), vtable: *const () }
-    let (data_ptr, vtable_ptr) = (waker.data(), waker.vtable() as *const _ as *const ());
+    if unsafe { *self.flags.get() } & (1 << 2) != 0 {
+      protocol_violation("FFIFuture polled after completion");
+    }
 
-    // 2. The "Fingerprint" Check
-    // This is essentially what will_wake does, but without the function call.
-    if { self.last_waker_data.load(Ordering::Acquire) != data_ptr.addr() } || {
-      self.last_waker_vtable.load(Ordering::Acquire) != vtable_ptr.addr()
-    } {
-      {
-        self
-          .last_waker_data
-          .store(data_ptr.addr(), Ordering::Release);
-        self
-          .last_waker_vtable
-          .store(vtable_ptr.addr(), Ordering::Release);
-      }
-
-      let new_internal = ManuallyDrop::new(waker.clone());
-
-      (self.task._cb)(
-        self.task._state,
-        CBReason::Waker {
-          waker: CWaker {
-            data: new_internal.data(),
-            vtable: new_internal.vtable() as *const _ as _,
-          },
-        },
-      );
+    if let Some(waker) = self.last_waker.changed(cx.waker()) {
+      (self.task._cb)(self.task._state, CBReason::Waker { waker });
     }
 
     let out = (self.task._cb)(self.task._state, CBReason::PollCollect {});
 
-    if let MaybeData::Some(out) = out.output {
-      unsafe {
-        *self.flags.get() |= 1 << 2;
-      }
-
-      return Poll::Ready(out);
+    if out.flag != 0 {
+      protocol_violation("the foreign future had already been collected");
     }
 
-    if out.flag != 0 {
-      panic!("[ERR] ASYNCHRONOUS GLITCHING AT CALLING FFI ASYNC FUNCTION");
+    let out = match out.output {
+      MaybeData::None => return Poll::Pending,
+      MaybeData::Some(out) => Ok(out),
+      MaybeData::Err(err) => Err(FutureError::Failed(err)),
+      MaybeData::Panicked(message) => Err(FutureError::Panicked(message)),
+    };
+
+    unsafe {
+      *self.flags.get() |= 1 << 2;
     }
 
-    Poll::Pending
+    Poll::Ready(out)
   }
 }
 
-impl<T: FFISafe + Sized> Drop for FFIFuture<T> {
+impl<T: FFISafe + Sized, E: FFISafe> Drop for FFIFuture<T, E> {
   fn drop(&mut self) {
     if self.flags.get_mut().bitand(1 << 2) != 0 {
       cold_path();
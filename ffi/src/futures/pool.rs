@@ -0,0 +1,140 @@
+//! Allocations of the future & stream states
+//!
+//! They are allocated through salloc, so the last waker ref can free them from any
+//! dylib. With the `pool` feature, freed states are kept in per-thread free lists
+//! keyed by size class & handed out again before asking salloc.
+
+use std::ffi::c_void;
+
+/// Every block is at least this aligned, the states are `align(64)` anyway
+const MIN_ALIGN: usize = 64;
+
+#[cfg(feature = "pool")]
+mod lists {
+  use std::{cell::Cell, ffi::c_void, ptr};
+
+  use super::MIN_ALIGN;
+
+  /// Size classes are the powers of two from `MIN_ALIGN` up to `MIN_ALIGN << (CLASSES - 1)`
+  const CLASSES: usize = 7;
+  /// Blocks cached per class & thread, the rest goes back to salloc
+  const MAX_CACHED: usize = 256;
+
+  /// Intrusive, the next block is stored in the first word of each block
+  struct FreeLists {
+    heads: [Cell<*mut c_void>; CLASSES],
+    lens: [Cell<usize>; CLASSES],
+  }
+
+  impl Drop for FreeLists {
+    fn drop(&mut self) {
+      for head in &self.heads {
+        let mut block = head.replace(ptr::null_mut());
+
+        while !block.is_null() {
+          let next = unsafe { *(block as *mut *mut c_void) };
+          unsafe { salloc::aligned_free(block) };
+
+          block = next;
+        }
+      }
+    }
+  }
+
+  thread_local! {
+    static LISTS: FreeLists = const {
+      FreeLists {
+        heads: [const { Cell::new(ptr::null_mut()) }; CLASSES],
+        lens: [const { Cell::new(0) }; CLASSES],
+      }
+    };
+  }
+
+  /// The size class of `size`, `None` if it is too big to be pooled
+  pub(super) fn class(size: usize) -> Option<usize> {
+    let class = size.max(MIN_ALIGN).next_power_of_two().trailing_zeros() as usize
+      - MIN_ALIGN.trailing_zeros() as usize;
+
+    (class < CLASSES).then_some(class)
+  }
+
+  pub(super) const fn class_size(class: usize) -> usize {
+    MIN_ALIGN << class
+  }
+
+  pub(super) fn pop(class: usize) -> *mut c_void {
+    // The thread local may already be gone while a thread exits
+    LISTS
+      .try_with(|lists| {
+        let block = lists.heads[class].get();
+
+        if !block.is_null() {
+          lists.heads[class].set(unsafe { *(block as *mut *mut c_void) });
+          lists.lens[class].set(lists.lens[class].get() - 1);
+        }
+
+        block
+      })
+      .unwrap_or(ptr::null_mut())
+  }
+
+  /// Returns `false` if the block was not taken, it must be freed instead
+  pub(super) fn push(class: usize, block: *mut c_void) -> bool {
+    LISTS
+      .try_with(|lists| {
+        if lists.lens[class].get() >= MAX_CACHED {
+          return false;
+        }
+
+        unsafe { *(block as *mut *mut c_void) = lists.heads[class].get() };
+        lists.heads[class].set(block);
+        lists.lens[class].set(lists.lens[class].get() + 1);
+
+        true
+      })
+      .unwrap_or(false)
+  }
+}
+
+/// Allocates a block for a state of `size` & `align`
+pub(crate) fn alloc(size: usize, align: usize) -> *mut c_void {
+  #[cfg(feature = "pool")]
+  let size = match lists::class(size) {
+    Some(class) if align <= MIN_ALIGN => {
+      let block = lists::pop(class);
+
+      if !block.is_null() {
+        return block;
+      }
+
+      lists::class_size(class)
+    }
+    _ => size,
+  };
+
+  let ptr = unsafe { salloc::aligned_malloc(size, align.max(MIN_ALIGN)) };
+
+  if ptr.is_null() {
+    panic!("Allocation Failed");
+  }
+
+  ptr
+}
+
+/// Frees a block given out by [`alloc`] with the same `size` & `align`
+///
+/// # Safety
+///
+/// The block must not be used anymore
+pub(crate) unsafe fn free(ptr: *mut c_void, size: usize, align: usize) {
+  #[cfg(feature = "pool")]
+  if let Some(class) = lists::class(size)
+    && align <= MIN_ALIGN
+    && lists::push(class, ptr)
+  {
+    return;
+  }
+
+  _ = (size, align);
+  unsafe { salloc::aligned_free(ptr) };
+}
@@ -0,0 +1,263 @@
+use std::{
+  cell::UnsafeCell,
+  convert::Infallible,
+  ffi::c_void,
+  mem::{MaybeUninit, forget, transmute},
+  ptr,
+  sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::{
+    CBReason, CompletionFn, FutureTask, MaybeData, Result, State, atomiccw::AtomicFFICWaker,
+  },
+  string::str::SharableStr,
+};
+
+/// Nothing was written yet
+const EMPTY: u8 = 0;
+/// The resolver is writing the output
+const WRITING: u8 = 1;
+const READY: u8 = 2;
+/// The output was collected
+const TAKEN: u8 = 3;
+/// The promise was dropped, the resolver drops its output itself
+const CANCELLED: u8 = 4;
+
+/// A [`FutureTask`] completed by hand through its [`FfiResolver`], see [`promise`]
+pub type FfiPromise<T, E = Infallible> = FutureTask<T, E>;
+
+struct PromiseState<T, E> {
+  waker_atomic: AtomicFFICWaker,
+
+  status: AtomicU8,
+  /// `MaybeData::Some`, `Err` or `Panicked`, written once by the resolver
+  output: UnsafeCell<MaybeUninit<MaybeData<T, E>>>,
+
+  /// The callback & context of `CBReason::OnComplete`, written before `on_complete` is set
+  completion: UnsafeCell<(*const c_void, *mut c_void)>,
+  /// Whoever moves the status from READY to TAKEN after this is set calls the callback
+  on_complete: AtomicBool,
+}
+
+/// Creates a task which completes once the resolver is called, possibly from another
+/// thread
+///
+/// Dropping the resolver without calling it completes the task with
+/// `MaybeData::Panicked`.
+pub fn promise<T: FFISafe, E: FFISafe>() -> (FfiPromise<T, E>, FfiResolver<T, E>) {
+  let state = Box::into_raw(Box::new(PromiseState::<T, E> {
+    waker_atomic: AtomicFFICWaker::new(),
+    status: AtomicU8::new(EMPTY),
+    output: UnsafeCell::new(MaybeUninit::uninit()),
+    completion: UnsafeCell::new((ptr::null(), ptr::null_mut())),
+    on_complete: AtomicBool::new(false),
+  }));
+
+  // One reference for each side
+  unsafe { (*state).waker_atomic.inc() };
+
+  (
+    FutureTask {
+      _state: state as _,
+      _cb: poll_promise::<T, E>,
+    },
+    FfiResolver {
+      _state: state as _,
+      _resolve: resolve::<T, E>,
+      _reject: reject::<T, E>,
+      _drop: drop_resolver::<T, E>,
+    },
+  )
+}
+
+#[repr(C)]
+/// Completes an [`FfiPromise`], usable from C through its functions
+///
+/// Exactly one of `_resolve`, `_reject` or `_drop` must be called with `_state`,
+/// exactly once. `_resolve` & `_reject` return `false` if the promise was dropped,
+/// the value is dropped then.
+pub struct FfiResolver<T: FFISafe, E: FFISafe = Infallible> {
+  pub _state: State,
+  pub _resolve: extern "C" fn(State, T) -> bool,
+  pub _reject: extern "C" fn(State, E) -> bool,
+  pub _drop: extern "C" fn(State),
+}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for FfiResolver<T, E> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send, E: FFISafe + Send> Send for FfiResolver<T, E> {}
+
+impl<T: FFISafe, E: FFISafe> FfiResolver<T, E> {
+  /// Completes the promise with `value`, `false` if it was dropped
+  pub fn resolve(self, value: T) -> bool {
+    let resolved = (self._resolve)(self._state, value);
+    forget(self);
+
+    resolved
+  }
+
+  /// Fails the promise with `err`, `false` if it was dropped
+  pub fn reject(self, err: E) -> bool {
+    let rejected = (self._reject)(self._state, err);
+    forget(self);
+
+    rejected
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Drop for FfiResolver<T, E> {
+  fn drop(&mut self) {
+    (self._drop)(self._state);
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> PromiseState<T, E> {
+  /// Writes the output & releases the reference of the resolver
+  ///
+  /// # Safety
+  ///
+  /// Only called once, by the resolver
+  unsafe fn complete(state: State, output: MaybeData<T, E>) -> bool {
+    let this = unsafe { &*(state as *const Self) };
+
+    let completed = if this
+      .status
+      .compare_exchange(EMPTY, WRITING, Ordering::AcqRel, Ordering::Acquire)
+      .is_ok()
+    {
+      unsafe { (*this.output.get()).write(output) };
+
+      match this
+        .status
+        .compare_exchange(WRITING, READY, Ordering::SeqCst, Ordering::Acquire)
+      {
+        Ok(_) => {
+          if this.on_complete.load(Ordering::SeqCst) {
+            unsafe { Self::deliver(state) };
+          } else {
+            this.waker_atomic.wake();
+          }
+
+          true
+        }
+        // Cancelled while writing
+        Err(_) => {
+          unsafe { (*this.output.get()).assume_init_drop() };
+          false
+        }
+      }
+    } else {
+      drop(output);
+      false
+    };
+
+    unsafe { Self::release(state) };
+
+    completed
+  }
+
+  /// Calls the completion callback with the output, if nobody did yet
+  ///
+  /// # Safety
+  ///
+  /// `on_complete` must be set
+  unsafe fn deliver(state: State) {
+    let this = unsafe { &*(state as *const Self) };
+
+    if this
+      .status
+      .compare_exchange(READY, TAKEN, Ordering::AcqRel, Ordering::Acquire)
+      .is_err()
+    {
+      return;
+    }
+
+    let output = match unsafe { (*this.output.get()).assume_init_read() } {
+      MaybeData::Some(x) => Result::ready(x),
+      MaybeData::Err(e) => Result::failed(e),
+      MaybeData::Panicked(message) => Result::panicked(message),
+      MaybeData::None => unreachable!(),
+    };
+
+    let (callback, ctx) = unsafe { *this.completion.get() };
+    let callback: CompletionFn<T, E> = unsafe { transmute(callback) };
+
+    // The reference of the promise side, given up by `FutureTask::on_complete`
+    unsafe { Self::release(state) };
+
+    callback(ctx, output);
+  }
+
+  unsafe fn release(state: State) {
+    if unsafe { (*(state as *const Self)).waker_atomic.dec() } {
+      drop(unsafe { Box::from_raw(state as *mut Self) });
+    }
+  }
+}
+
+extern "C" fn resolve<T: FFISafe, E: FFISafe>(state: State, value: T) -> bool {
+  unsafe { PromiseState::<T, E>::complete(state, MaybeData::Some(value)) }
+}
+
+extern "C" fn reject<T: FFISafe, E: FFISafe>(state: State, err: E) -> bool {
+  unsafe { PromiseState::<T, E>::complete(state, MaybeData::Err(err)) }
+}
+
+extern "C" fn drop_resolver<T: FFISafe, E: FFISafe>(state: State) {
+  let message = SharableStr::create("FfiResolver dropped without resolving the promise");
+
+  unsafe { PromiseState::<T, E>::complete(state, MaybeData::Panicked(message)) };
+}
+
+extern "C" fn poll_promise<T: FFISafe, E: FFISafe>(
+  state_ptr: State,
+  action: CBReason,
+) -> Result<T, E> {
+  let state = unsafe { &mut *(state_ptr as *mut PromiseState<T, E>) };
+
+  match action {
+    CBReason::SealWakerVTable { vtable } => state.waker_atomic.set_vtable(vtable),
+    CBReason::Waker { waker } => state.waker_atomic.update(waker),
+    CBReason::PollCollect => {
+      if state
+        .status
+        .compare_exchange(READY, TAKEN, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+      {
+        return Result::pending();
+      }
+
+      return match unsafe { (*state.output.get()).assume_init_read() } {
+        MaybeData::Some(x) => Result::ready(x),
+        MaybeData::Err(e) => Result::failed(e),
+        MaybeData::Panicked(message) => Result::panicked(message),
+        MaybeData::None => unreachable!(),
+      };
+    }
+    CBReason::Cleanup | CBReason::Abort => unsafe {
+      if state.status.swap(CANCELLED, Ordering::AcqRel) == READY {
+        (*state.output.get()).assume_init_drop();
+      }
+
+      PromiseState::<T, E>::release(state_ptr);
+    },
+    CBReason::OnComplete { callback, ctx } => unsafe {
+      // Either we see READY here, or the resolver sees `on_complete` right after
+      // writing READY, so exactly one of us delivers the output
+      *state.completion.get() = (callback, ctx);
+      state.on_complete.store(true, Ordering::SeqCst);
+
+      if state.status.load(Ordering::SeqCst) == READY {
+        PromiseState::<T, E>::deliver(state_ptr);
+      }
+    },
+  }
+
+  Result::pending()
+}
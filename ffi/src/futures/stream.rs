@@ -0,0 +1,202 @@
+use std::{
+  convert::Infallible,
+  mem::{ManuallyDrop, forget},
+  pin::Pin,
+  ptr::addr_of,
+  task::{Context, Poll},
+};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  futures::{CWaker, FutureError, LastWaker, State, WAKER_VTABLE, WakerVTable},
+  string::str::SharableStr,
+};
+
+#[repr(C)]
+/// Same as [`super::CBReason`], but asks for the next item instead of the output
+pub enum StreamReason {
+  /// It is expected to make NO asynchronous progress
+  /// during this stage as otherwise, it will make things messy
+  SealWakerVTable {
+    /// Please make sure to copy the data from here!!
+    vtable: *const WakerVTable,
+  },
+
+  PollNext,
+  /// Function to call to wake it up, see [`super::CBReason::Waker`]
+  Waker {
+    waker: CWaker,
+  },
+
+  Abort,
+  Cleanup,
+}
+
+#[repr(C)]
+pub enum StreamItem<T, E> {
+  /// No item is available yet, the waker is called once one is
+  Pending,
+  Ready(T),
+  /// An item failed, the stream goes on
+  Err(E),
+  /// The stream ended, every next poll returns this again
+  Finished,
+  /// Polling the stream panicked, this is the panic message
+  ///
+  /// The stream has been dropped & is finished
+  Panicked(SharableStr),
+}
+
+pub type StreamCallback<T, E = Infallible> = extern "C" fn(State, StreamReason) -> StreamItem<T, E>;
+
+#[repr(C)]
+/// An owned handle to a stream living in another dylib
+///
+/// Dropping it aborts the stream, see [`StreamReason::Abort`]. It is `Send` like a
+/// [`super::FutureTask`], see [`super::local::LocalFfiStream`] for streams which are not.
+pub struct FfiStream<T: FFISafe, E: FFISafe = Infallible> {
+  pub(crate) _state: State,
+  pub(crate) _cb: StreamCallback<T, E>,
+}
+
+// Same as `FutureTask`, only `Send` streams or the unsafe `from_raw` make one
+unsafe impl<T: FFISafe + Send, E: FFISafe + Send> Send for FfiStream<T, E> {}
+
+unsafe impl<T: FFISafe, E: FFISafe> FFISafe for FfiStream<T, E> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> FfiStream<T, E> {
+  /// Gives up ownership, returning the state and its callback
+  ///
+  /// The stream must be given back to [`FfiStream::from_raw`], possibly in another dylib,
+  /// to be aborted or consumed.
+  pub fn into_raw(self) -> (State, StreamCallback<T, E>) {
+    let out = (self._state, self._cb);
+    forget(self);
+
+    out
+  }
+
+  /// Takes ownership of the parts returned by [`FfiStream::into_raw`], `None` if the
+  /// state is null
+  ///
+  /// # Safety
+  ///
+  /// The parts must come from `into_raw` of a `FfiStream<T, E>` built with the same
+  /// saffi version, and nobody else may own them. They must not come from a
+  /// [`super::local::LocalFfiStream`], as the stream is `Send`.
+  pub unsafe fn from_raw(state: State, cb: StreamCallback<T, E>) -> Option<Self> {
+    if state.is_null() {
+      return None;
+    }
+
+    Some(Self {
+      _state: state,
+      _cb: cb,
+    })
+  }
+
+  /// Gives up ownership without ever aborting the stream, returning its state
+  pub fn leak(self) -> State {
+    self.into_raw().0
+  }
+
+  #[inline(always)]
+  /// Returns the same state as [`FfiStream::into_raw`] without giving up ownership
+  pub const fn as_raw(&self) -> State {
+    self._state
+  }
+
+  /// Lends the parts of [`FfiStream::into_raw`] to `f`, see [`super::FutureTask::with_raw`]
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(State, StreamCallback<T, E>) -> R) -> R {
+    f(self._state, self._cb)
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Drop for FfiStream<T, E> {
+  fn drop(&mut self) {
+    (self._cb)(self._state, StreamReason::Abort);
+  }
+}
+
+/// Consumes a [`FfiStream`] as a [`Stream`] of `Ok` items or `Err` if an item failed
+///
+/// A panic of the foreign stream is sent as a last `Err(FutureError::Panicked)`.
+pub struct FFIStream<T: FFISafe, E: FFISafe = Infallible> {
+  /// Aborted or cleaned up by our own drop
+  stream: ManuallyDrop<FfiStream<T, E>>,
+  finished: bool,
+
+  last_waker: LastWaker,
+}
+
+impl<T: FFISafe, E: FFISafe> FFIStream<T, E> {
+  pub fn new(stream: FfiStream<T, E>) -> Self {
+    (stream._cb)(
+      stream._state,
+      StreamReason::SealWakerVTable {
+        vtable: addr_of!(WAKER_VTABLE),
+      },
+    );
+
+    Self {
+      stream: ManuallyDrop::new(stream),
+      finished: false,
+      last_waker: LastWaker::new(),
+    }
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Stream for FFIStream<T, E> {
+  type Item = core::result::Result<T, FutureError<E>>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    if self.finished {
+      return Poll::Ready(None);
+    }
+
+    if let Some(waker) = self.last_waker.changed(cx.waker()) {
+      (self.stream._cb)(self.stream._state, StreamReason::Waker { waker });
+    }
+
+    match (self.stream._cb)(self.stream._state, StreamReason::PollNext) {
+      StreamItem::Pending => Poll::Pending,
+      StreamItem::Ready(item) => Poll::Ready(Some(Ok(item))),
+      StreamItem::Err(err) => Poll::Ready(Some(Err(FutureError::Failed(err)))),
+      StreamItem::Finished => {
+        self.finished = true;
+
+        Poll::Ready(None)
+      }
+      StreamItem::Panicked(message) => {
+        self.finished = true;
+
+        Poll::Ready(Some(Err(FutureError::Panicked(message))))
+      }
+    }
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> FusedStream for FFIStream<T, E> {
+  fn is_terminated(&self) -> bool {
+    self.finished
+  }
+}
+
+impl<T: FFISafe, E: FFISafe> Drop for FFIStream<T, E> {
+  fn drop(&mut self) {
+    let reason = if self.finished {
+      StreamReason::Cleanup
+    } else {
+      StreamReason::Abort
+    };
+
+    (self.stream._cb)(self.stream._state, reason);
+  }
+}
@@ -0,0 +1,322 @@
+use core::ffi::c_void;
+use core::{mem::MaybeUninit, ptr};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::RTBox, vector::Vector,
+};
+
+/// Marks the end of the free list
+const NO_FREE: u32 = u32::MAX;
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A handle into a [`HandleTable`], to pass around instead of a raw pointer
+///
+/// This is the generation of the slot in the upper 32 bits and its index in the lower
+/// 32 bits. Once the value is removed, the slot moves to the next generation, so a stale
+/// handle is simply not found instead of being a use-after-free. `0` is never a valid handle.
+pub struct Handle(u64);
+
+unsafe impl FFISafe for Handle {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl Handle {
+  pub const NULL: Self = Self(0);
+
+  #[inline(always)]
+  const fn new(index: u32, generation: u32) -> Self {
+    Self(((generation as u64) << 32) | index as u64)
+  }
+
+  #[inline(always)]
+  pub const fn index(self) -> u32 {
+    self.0 as u32
+  }
+
+  #[inline(always)]
+  pub const fn generation(self) -> u32 {
+    (self.0 >> 32) as u32
+  }
+
+  #[inline(always)]
+  pub const fn to_raw(self) -> u64 {
+    self.0
+  }
+
+  #[inline(always)]
+  pub const fn from_raw(raw: u64) -> Self {
+    Self(raw)
+  }
+}
+
+#[repr(C)]
+/// The generation is odd while the slot is occupied
+struct Slot<T> {
+  generation: u32,
+  /// Next vacant slot, only meaningful while this one is vacant
+  next_free: u32,
+  value: MaybeUninit<T>,
+}
+
+unsafe impl<T: FFISafe> FFISafe for Slot<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl<T> Slot<T> {
+  #[inline(always)]
+  const fn occupied(&self) -> bool {
+    self.generation & 1 == 1
+  }
+}
+
+struct Inner<T: FFISafe> {
+  slots: Vector<Slot<T>>,
+  free_head: u32,
+  len: usize,
+}
+
+impl<T: FFISafe> Inner<T> {
+  #[inline(always)]
+  fn slot(&self, handle: Handle) -> Option<&Slot<T>> {
+    let slot = self.slots.get(handle.index() as usize)?;
+
+    (slot.occupied() && slot.generation == handle.generation()).then_some(slot)
+  }
+}
+
+/// A thread-safe slot map, handing out [`Handle`]s for the values inserted into it
+///
+/// The slots are stored in a [`Vector`] and reused once their value is removed.
+/// A slot whose generation would overflow is retired instead of being reused.
+pub struct HandleTable<T: FFISafe> {
+  inner: RwLock<Inner<T>>,
+}
+
+// Only ever accessed through a pointer from foreign code
+unsafe impl<T: FFISafe> FFISafe for HandleTable<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send> Send for HandleTable<T> {}
+unsafe impl<T: FFISafe + Send + Sync> Sync for HandleTable<T> {}
+
+impl<T: FFISafe> Default for HandleTable<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: FFISafe> HandleTable<T> {
+  pub fn new() -> Self {
+    Self {
+      inner: RwLock::new(Inner {
+        slots: Vector::new(),
+        free_head: NO_FREE,
+        len: 0,
+      }),
+    }
+  }
+
+  // A panic while the lock is held can only come from the closure of `with` or
+  // `with_mut`, or from a failed allocation. The table stays consistent either way,
+  // so a poisoned lock is simply recovered, as in `Drop`.
+  #[inline(always)]
+  fn read(&self) -> RwLockReadGuard<'_, Inner<T>> {
+    self.inner.read().unwrap_or_else(|e| e.into_inner())
+  }
+
+  #[inline(always)]
+  fn write(&self) -> RwLockWriteGuard<'_, Inner<T>> {
+    self.inner.write().unwrap_or_else(|e| e.into_inner())
+  }
+
+  pub fn len(&self) -> usize {
+    self.read().len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// # Panics
+  ///
+  /// If the table is full, see [`HandleTable::try_insert`]
+  pub fn insert(&self, value: T) -> Handle {
+    match self.try_insert(value) {
+      Ok(handle) => handle,
+      Err(_) => panic!("Too many handles"),
+    }
+  }
+
+  /// Same as [`HandleTable::insert`], but gives the value back if the table is full
+  pub fn try_insert(&self, value: T) -> Result<Handle, T> {
+    let mut inner = self.write();
+
+    let index = inner.free_head;
+
+    if index == NO_FREE {
+      let index = inner.slots.len();
+
+      if index >= NO_FREE as usize {
+        return Err(value);
+      }
+
+      inner.slots.push(Slot {
+        generation: 1,
+        next_free: NO_FREE,
+        value: MaybeUninit::new(value),
+      });
+      inner.len += 1;
+
+      return Ok(Handle::new(index as u32, 1));
+    }
+
+    let slot = &mut (*inner.slots)[index as usize];
+
+    let next_free = slot.next_free;
+    slot.generation += 1;
+    slot.value.write(value);
+
+    let handle = Handle::new(index, slot.generation);
+    inner.free_head = next_free;
+    inner.len += 1;
+
+    Ok(handle)
+  }
+
+  /// Whether the handle still refers to a value
+  pub fn contains(&self, handle: Handle) -> bool {
+    self.read().slot(handle).is_some()
+  }
+
+  /// Calls `f` with the value of the handle, while the table is read-locked
+  pub fn with<R>(&self, handle: Handle, f: impl FnOnce(&T) -> R) -> Option<R> {
+    let inner = self.read();
+    let slot = inner.slot(handle)?;
+
+    Some(f(unsafe { slot.value.assume_init_ref() }))
+  }
+
+  /// Calls `f` with the value of the handle, while the table is write-locked
+  pub fn with_mut<R>(&self, handle: Handle, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    let mut inner = self.write();
+    inner.slot(handle)?;
+
+    let slot = &mut (*inner.slots)[handle.index() as usize];
+
+    Some(f(unsafe { slot.value.assume_init_mut() }))
+  }
+
+  pub fn get(&self, handle: Handle) -> Option<T>
+  where
+    T: Clone,
+  {
+    self.with(handle, T::clone)
+  }
+
+  /// Removes the value, every copy of the handle becomes invalid
+  pub fn remove(&self, handle: Handle) -> Option<T> {
+    let mut inner = self.write();
+    inner.slot(handle)?;
+
+    let free_head = inner.free_head;
+    let slot = &mut (*inner.slots)[handle.index() as usize];
+
+    let value = unsafe { slot.value.assume_init_read() };
+    slot.generation = slot.generation.wrapping_add(1);
+
+    // The generation wrapped around, reusing the slot could revive stale handles
+    if slot.generation != 0 {
+      slot.next_free = free_head;
+      inner.free_head = handle.index();
+    }
+
+    inner.len -= 1;
+
+    Some(value)
+  }
+}
+
+impl<T: FFISafe> Drop for HandleTable<T> {
+  fn drop(&mut self) {
+    let inner = self.inner.get_mut().unwrap_or_else(|e| e.into_inner());
+
+    for slot in inner.slots.iter_mut() {
+      if slot.occupied() {
+        unsafe { slot.value.assume_init_drop() };
+      }
+    }
+  }
+}
+
+/// The handle table for foreign code, which stores plain pointers
+pub type CHandleTable = HandleTable<*mut c_void>;
+
+/// Creates a handle table, free it with [`saffi_handle_table_free`]
+#[unsafe(no_mangle)]
+pub extern "C" fn saffi_handle_table_new() -> *mut CHandleTable {
+  match RTBox::new(CHandleTable::new()) {
+    Some(table) => table.into_raw(),
+    None => ptr::null_mut(),
+  }
+}
+
+/// # Safety
+///
+/// `table` must have been created by [`saffi_handle_table_new`] and must not be used afterwards
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_handle_table_free(table: *mut CHandleTable) {
+  unsafe { drop(RTBox::from_raw(table)) };
+}
+
+/// Returns the handle for `ptr`, or 0 if the table is full
+///
+/// # Safety
+///
+/// `table` must be a live table from [`saffi_handle_table_new`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_handle_insert(table: *const CHandleTable, ptr: *mut c_void) -> u64 {
+  unsafe { (*table).try_insert(ptr) }
+    .unwrap_or(Handle::NULL)
+    .to_raw()
+}
+
+/// Returns the pointer of the handle, or null if the handle is stale
+///
+/// # Safety
+///
+/// `table` must be a live table from [`saffi_handle_table_new`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_handle_get(table: *const CHandleTable, handle: u64) -> *mut c_void {
+  unsafe { (*table).get(Handle::from_raw(handle)) }.unwrap_or(ptr::null_mut())
+}
+
+/// # Safety
+///
+/// `table` must be a live table from [`saffi_handle_table_new`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_handle_is_valid(table: *const CHandleTable, handle: u64) -> bool {
+  unsafe { (*table).contains(Handle::from_raw(handle)) }
+}
+
+/// Removes the handle, returning its pointer or null if the handle is stale
+///
+/// # Safety
+///
+/// `table` must be a live table from [`saffi_handle_table_new`]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn saffi_handle_remove(
+  table: *const CHandleTable,
+  handle: u64,
+) -> *mut c_void {
+  unsafe { (*table).remove(Handle::from_raw(handle)) }.unwrap_or(ptr::null_mut())
+}
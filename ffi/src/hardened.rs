@@ -0,0 +1,149 @@
+use core::ffi::c_void;
+
+/// Written at the start of every header, right before the type tag
+#[cfg(feature = "hardened")]
+pub(crate) const CANARY: u64 = 0x5AFF_1CA7_C0DE_5AFE;
+/// Written over the canary once the allocation is freed
+#[cfg(feature = "hardened")]
+pub(crate) const FREED: u64 = 0xF4EE_D5AF_F1DE_AD00;
+/// The byte the rest of a freed allocation is filled with
+#[cfg(feature = "hardened")]
+pub(crate) const POISON: u8 = 0xDD;
+
+pub(crate) const TAG_RTBOX: u64 = 1;
+pub(crate) const TAG_VECTOR: u64 = 2;
+pub(crate) const TAG_STR: u64 = 3;
+pub(crate) const TAG_DEQUE: u64 = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[cfg(feature = "hardened")]
+/// The first field of the saffi headers when the `hardened` feature is enabled
+///
+/// The headers of `RTBoxWrapper`, `VectorHeaderVTable` and the `SharableStr` header
+/// all grow by this same amount, so they can still be converted into each other in place.
+pub(crate) struct Guard {
+  canary: u64,
+  tag: u64,
+}
+
+#[cfg(feature = "hardened")]
+impl Guard {
+  #[inline(always)]
+  pub(crate) const fn new(tag: u64) -> Self {
+    Self {
+      canary: CANARY,
+      tag,
+    }
+  }
+}
+
+#[cfg(feature = "hardened")]
+const fn kind(tag: u64) -> &'static str {
+  match tag {
+    TAG_RTBOX => "RTBox",
+    TAG_VECTOR => "Vector",
+    TAG_STR => "SharableStr",
+    TAG_DEQUE => "FfiVecDeque",
+    _ => "unknown container",
+  }
+}
+
+#[cold]
+#[cfg(feature = "hardened")]
+#[track_caller]
+fn corrupted(found: Guard, tag: u64) -> ! {
+  if found.canary == FREED {
+    panic!("saffi: {} used after it was freed", kind(tag));
+  }
+
+  if found.canary != CANARY {
+    panic!(
+      "saffi: {} has a corrupted header, the canary is {:#x}",
+      kind(tag),
+      found.canary
+    );
+  }
+
+  panic!(
+    "saffi: expected the header of a {} but found the one of a {}",
+    kind(tag),
+    kind(found.tag)
+  );
+}
+
+#[inline(always)]
+#[track_caller]
+/// Checks the guard at the start of `header`, a no-op unless `hardened` is enabled
+///
+/// # Safety
+///
+/// `header` must be readable for the size of [`Guard`]
+pub(crate) unsafe fn verify(header: *const c_void, tag: u64) {
+  #[cfg(feature = "hardened")]
+  unsafe {
+    let found = *(header as *const Guard);
+
+    if found.canary != CANARY || found.tag != tag {
+      corrupted(found, tag);
+    }
+  }
+
+  #[cfg(not(feature = "hardened"))]
+  let _ = (header, tag);
+}
+
+#[inline(always)]
+/// Changes the tag of the guard, when the allocation is handed to another container
+///
+/// # Safety
+///
+/// `header` must be writable for the size of [`Guard`]
+pub(crate) unsafe fn retag(header: *mut c_void, tag: u64) {
+  #[cfg(feature = "hardened")]
+  unsafe {
+    *(header as *mut Guard) = Guard::new(tag);
+  }
+
+  #[cfg(not(feature = "hardened"))]
+  let _ = (header, tag);
+}
+
+#[inline(always)]
+/// Poisons `size` bytes of an allocation that is about to be freed, `base` being
+/// the header
+///
+/// # Safety
+///
+/// `base` must be writable for `size` bytes, which must be at least the size of [`Guard`]
+pub(crate) unsafe fn poison(base: *mut c_void, size: usize) {
+  #[cfg(feature = "hardened")]
+  unsafe {
+    core::ptr::write_bytes(base as *mut u8, POISON, size);
+
+    (*(base as *mut Guard)).canary = FREED;
+  }
+
+  #[cfg(not(feature = "hardened"))]
+  let _ = (base, size);
+}
+
+#[inline(always)]
+#[track_caller]
+/// Validates a `known_*` value given to an `*_aided` method against the header
+pub(crate) fn check_known(name: &str, known: Option<usize>, actual: impl FnOnce() -> usize) {
+  #[cfg(feature = "hardened")]
+  if let Some(known) = known {
+    let actual = actual();
+
+    if known != actual {
+      panic!(
+        "saffi: {} is {} but the header says {}",
+        name, known, actual
+      );
+    }
+  }
+
+  #[cfg(not(feature = "hardened"))]
+  let _ = (name, known, actual);
+}
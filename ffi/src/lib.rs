@@ -1,7 +1,11 @@
-use core::ffi::c_void;
+#![feature(const_type_name, ptr_metadata, unsize)]
+
+use core::{convert::Infallible, ffi::c_void, mem::MaybeUninit};
 
 pub mod boxed;
 pub mod futures;
+pub mod handle;
+mod hardened;
 pub mod string;
 pub mod vector;
 
@@ -43,7 +47,8 @@ ffisafe! {
   usize,
   isize,
   c_void,
-  ()
+  (),
+  Infallible
 }
 
 unsafe impl<T> FFISafe for *const T {
@@ -56,6 +61,11 @@ unsafe impl<T> FFISafe for *mut T {
     I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
   }
 }
+unsafe impl<T: FFISafe> FFISafe for MaybeUninit<T> {
+  fn i_am_ffisafe() -> IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
 
 #[cfg(test)]
 pub mod tests;
@@ -3,22 +3,50 @@ use core::{
   ptr, slice,
   str::{self, Utf8Error},
 };
-use std::{mem::offset_of, ptr::NonNull};
+use std::{
+  mem::{forget, offset_of},
+  ptr::NonNull,
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  hardened::{self, TAG_STR, TAG_VECTOR},
+  vector::{self, Vector},
+};
 
 #[repr(C)]
+/// This is the same header as `VectorHeaderVTable<u8>`, so a [`SharableStr`] and
+/// a `Vector<u8>` can be converted into each other without reallocating
 pub struct SharedStrVTHelper {
+  #[cfg(feature = "hardened")]
+  _guard: hardened::Guard,
+
   len: usize,
+  cap: usize,
   raw: (),
 }
 
 const OFFSET: isize = offset_of!(SharedStrVTHelper, raw) as isize;
 const NEG_OFFSET: isize = -(offset_of!(SharedStrVTHelper, raw) as isize);
 
+const _SAFETY: () = assert!(OFFSET == vector::data_offset::<u8>());
+
 #[repr(C)]
 pub struct SharableStr {
   ptr: NonNull<u8>,
 }
 
+unsafe impl FFISafe for SharableStr {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+// Uniquely owned & immutable, like a `Box<str>`
+unsafe impl Send for SharableStr {}
+unsafe impl Sync for SharableStr {}
+
 impl SharableStr {
   pub fn create(data: &str) -> Self {
     let length = data.len();
@@ -30,7 +58,10 @@ impl SharableStr {
       ptr::write(
         _raw as *mut SharedStrVTHelper,
         SharedStrVTHelper {
+          #[cfg(feature = "hardened")]
+          _guard: hardened::Guard::new(TAG_STR),
           len: length,
+          cap: length,
           raw: (),
         },
       );
@@ -48,20 +79,113 @@ impl SharableStr {
     }
   }
 
-  pub const fn into_raw(&mut self) -> *mut u8 {
-    self.ptr.as_ptr()
+  /// Converts a byte vector into a string without copying
+  ///
+  /// The bytes are validated to be UTF-8, the vector is given back on failure.
+  pub fn from_utf8(bytes: Vector<u8>) -> Result<Self, FromUtf8Error> {
+    match str::from_utf8(&bytes) {
+      Ok(_) => Ok(unsafe { Self::from_utf8_unchecked(bytes) }),
+      Err(error) => Err(FromUtf8Error { bytes, error }),
+    }
   }
 
+  /// Converts a byte vector into a string without copying or validating it
+  ///
+  /// # Safety
+  ///
+  /// The bytes must be valid UTF-8
+  pub unsafe fn from_utf8_unchecked(bytes: Vector<u8>) -> Self {
+    unsafe {
+      let ptr = bytes.into_raw();
+      hardened::retag(ptr.byte_offset(NEG_OFFSET) as _, TAG_STR);
+
+      Self {
+        ptr: NonNull::new_unchecked(ptr),
+      }
+    }
+  }
+
+  #[inline(always)]
+  #[track_caller]
+  /// Checks the header canary, see the `hardened` feature
+  fn verify(&self) {
+    unsafe { hardened::verify(self.ptr.as_ptr().byte_offset(NEG_OFFSET) as _, TAG_STR) };
+  }
+
+  /// Converts this string into a byte vector without copying
+  pub fn into_bytes(self) -> Vector<u8> {
+    self.verify();
+
+    let ptr = self.ptr;
+    forget(self);
+
+    // SAFETY: The header is the same as the one of `Vector<u8>`
+    unsafe {
+      hardened::retag(ptr.as_ptr().byte_offset(NEG_OFFSET) as _, TAG_VECTOR);
+
+      Vector::from_nonnull(ptr)
+    }
+  }
+
+  /// Gives up ownership, returning the pointer to the first byte
+  ///
+  /// The string must be given back to [`SharableStr::from_raw`], possibly in another
+  /// dylib, to be freed.
+  pub fn into_raw(self) -> *mut u8 {
+    let data = self.ptr.as_ptr();
+    forget(self);
+
+    data
+  }
+
+  #[track_caller]
+  /// Takes ownership of a pointer returned by [`SharableStr::into_raw`], `None` if it
+  /// is null
+  ///
+  /// # Safety
+  ///
+  /// The pointer must come from `into_raw` of a `SharableStr` built with the same saffi
+  /// version, and nobody else may own it.
   pub unsafe fn from_raw(ptr: *mut u8) -> Option<Self> {
-    Some(Self {
-      ptr: NonNull::new(ptr)?,
-    })
+    let ptr = NonNull::new(ptr)?;
+    unsafe { hardened::verify(ptr.as_ptr().byte_offset(NEG_OFFSET) as _, TAG_STR) };
+
+    Some(Self { ptr })
   }
 
+  /// Same as [`SharableStr::from_raw`] for a pointer that is known not to be null
+  ///
+  /// # Safety
+  ///
+  /// See [`SharableStr::from_raw`]
   pub const unsafe fn from_nonnull(ptr: NonNull<u8>) -> Self {
     Self { ptr }
   }
 
+  /// Gives up ownership without ever freeing the string, returning it
+  pub fn leak<'a>(self) -> &'a mut str {
+    let len = self.len();
+
+    unsafe { str::from_utf8_unchecked_mut(slice::from_raw_parts_mut(self.into_raw(), len)) }
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`SharableStr::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the string is dropped.
+  pub const fn as_raw(&self) -> *const u8 {
+    self.ptr.as_ptr()
+  }
+
+  /// Lends the pointer of [`SharableStr::into_raw`] to `f`, e.g. to hand it to foreign
+  /// code for the duration of a call
+  ///
+  /// `f` may read the bytes through it, but must neither free it nor keep it after
+  /// returning. The bytes must stay valid UTF-8.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut u8) -> R) -> R {
+    f(self.ptr.as_ptr())
+  }
+
   /// Please note that the lifetime <'a> refers to the lifetime of the
   /// const reference
   /// Please ensure that the const reference stays as long as <'a>
@@ -70,7 +194,7 @@ impl SharableStr {
   pub const unsafe fn as_str_unchecked<'a>(data: &Self) -> &'a str {
     let data_ptr = data.ptr.as_ptr();
 
-    let len = unsafe { ptr::read((data_ptr as *mut usize).byte_offset(NEG_OFFSET)) };
+    let len = unsafe { (*(data_ptr.byte_offset(NEG_OFFSET) as *const SharedStrVTHelper)).len };
 
     unsafe { str::from_utf8_unchecked(slice::from_raw_parts(data_ptr, len)) }
   }
@@ -81,24 +205,88 @@ impl SharableStr {
   pub const unsafe fn as_str<'a>(data: &Self) -> Result<&'a str, Utf8Error> {
     let data_ptr = data.ptr.as_ptr();
 
-    let len = unsafe { ptr::read((data_ptr as *mut usize).byte_offset(NEG_OFFSET)) };
+    let len = unsafe { (*(data_ptr.byte_offset(NEG_OFFSET) as *const SharedStrVTHelper)).len };
 
     unsafe { str::from_utf8(slice::from_raw_parts(data_ptr, len)) }
   }
 }
 
+impl TryFrom<Vector<u8>> for SharableStr {
+  type Error = FromUtf8Error;
+
+  fn try_from(value: Vector<u8>) -> Result<Self, Self::Error> {
+    Self::from_utf8(value)
+  }
+}
+
+impl From<SharableStr> for Vector<u8> {
+  fn from(value: SharableStr) -> Self {
+    value.into_bytes()
+  }
+}
+
+/// The error returned by [`SharableStr::from_utf8`], this holds the vector that
+/// failed to convert
+pub struct FromUtf8Error {
+  bytes: Vector<u8>,
+  error: Utf8Error,
+}
+
+impl FromUtf8Error {
+  pub fn utf8_error(&self) -> Utf8Error {
+    self.error
+  }
+
+  pub fn into_bytes(self) -> Vector<u8> {
+    self.bytes
+  }
+}
+
+impl core::fmt::Debug for FromUtf8Error {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("FromUtf8Error")
+      .field("bytes", &&*self.bytes)
+      .field("error", &self.error)
+      .finish()
+  }
+}
+
+impl core::fmt::Display for FromUtf8Error {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Display::fmt(&self.error, f)
+  }
+}
+
+impl std::error::Error for FromUtf8Error {}
+
 impl Deref for SharableStr {
   type Target = str;
 
   fn deref(&self) -> &Self::Target {
+    self.verify();
+
     unsafe { Self::as_str(self).expect("Invalid UTF8 Data") }
   }
 }
 
+impl core::fmt::Debug for SharableStr {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    core::fmt::Debug::fmt(&**self, f)
+  }
+}
+
 impl Drop for SharableStr {
   fn drop(&mut self) {
+    self.verify();
+
     unsafe {
-      salloc::aligned_free(self.ptr.as_ptr().byte_offset(NEG_OFFSET) as _);
+      let base = self.ptr.as_ptr().byte_offset(NEG_OFFSET);
+
+      hardened::poison(
+        base as _,
+        OFFSET as usize + (*(base as *const SharedStrVTHelper)).cap,
+      );
+      salloc::aligned_free(base as _);
     }
   }
 }
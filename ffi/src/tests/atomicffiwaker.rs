@@ -8,7 +8,7 @@ use crate::futures::{FFIFuture, implements::create_future};
 async fn test_rt() {
   let fut = create_future(hello());
 
-  let out = FFIFuture::new(fut).await;
+  let out = FFIFuture::new(fut).await.unwrap();
 
   assert!(out == 64);
 }
@@ -0,0 +1,107 @@
+use std::{ffi::c_void, fmt::Display};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::{RTBox, foreign::saffi_rtbox_clone},
+  vector::Vector,
+};
+
+#[derive(Clone)]
+#[repr(C)]
+struct Name(RTBox<str>);
+
+unsafe impl FFISafe for Name {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl Display for Name {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", &*self.0)
+  }
+}
+
+#[repr(C)]
+struct Unique(u64);
+
+unsafe impl FFISafe for Unique {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+unsafe extern "C" fn noop_delete(_data: *mut c_void, _ctx: *mut c_void) {}
+
+#[test]
+fn test_clone() {
+  let boxed = RTBox::new_cloneable(5u64).unwrap();
+  let mut cloned = boxed.clone();
+  *cloned = 6;
+
+  assert_eq!((*boxed, *cloned), (5, 6));
+
+  let slice = RTBox::<[u64]>::new_slice(&[1, 2, 3]).unwrap();
+  assert_eq!(&*slice.clone(), &[1, 2, 3]);
+
+  let string = RTBox::<str>::new_str("cloned").unwrap();
+  assert_eq!(&*string.clone(), "cloned");
+
+  let names = RTBox::new_cloneable(Name(string)).unwrap();
+  let other = names.clone();
+  assert_ne!(other.0.as_ptr(), names.0.as_ptr());
+}
+
+#[test]
+fn test_clone_without_entry() {
+  // Cloned on the Rust side into a cloneable box
+  let boxed = RTBox::new(5u64).unwrap();
+  let cloned = boxed.clone();
+  assert_eq!(*cloned, 5);
+  assert!(cloned.try_clone().is_some());
+
+  let mut vector = Vector::new();
+  vector.extend([Name(RTBox::new_str("a").unwrap())]);
+  let slice = RTBox::<[Name]>::from(vector);
+  assert!(slice.try_clone().is_none());
+  assert_eq!(slice.clone()[0].to_string(), "a");
+
+  let foreign = unsafe { RTBox::with_deleter(7u64, noop_delete) }.unwrap();
+  assert_eq!(*foreign.clone(), 7);
+}
+
+#[test]
+fn test_try_clone() {
+  assert!(RTBox::new(Unique(1)).unwrap().try_clone().is_none());
+  // Only the cloneable constructors fill the clone entry in
+  assert!(RTBox::new(1u64).unwrap().try_clone().is_none());
+
+  let dynamic =
+    RTBox::<dyn Display>::new_unsize_cloneable(Name(RTBox::<str>::new_str("dyn").unwrap()))
+      .unwrap();
+  assert_eq!(dynamic.try_clone().unwrap().to_string(), "dyn");
+
+  // Cloning a foreign resource would delete it twice
+  let foreign = unsafe { RTBox::with_deleter(7u64, noop_delete) }.unwrap();
+  assert!(foreign.try_clone().is_none());
+}
+
+#[test]
+fn test_c_clone() {
+  let boxed = RTBox::new_cloneable(9u64).unwrap().into_thin_raw();
+
+  unsafe {
+    let cloned = saffi_rtbox_clone(boxed);
+    assert_ne!(cloned, boxed);
+
+    let (boxed, cloned) = (
+      RTBox::<u64>::from_thin_raw(boxed).unwrap(),
+      RTBox::<u64>::from_thin_raw(cloned).unwrap(),
+    );
+    assert_eq!(*boxed, *cloned);
+
+    let unique = RTBox::new(Unique(2)).unwrap();
+    assert!(saffi_rtbox_clone(unique.as_ptr() as _).is_null());
+  }
+}
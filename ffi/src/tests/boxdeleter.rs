@@ -0,0 +1,91 @@
+use std::{
+  ffi::c_void,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::boxed::{
+  RTBox,
+  foreign::{saffi_rtbox_adopt, saffi_rtbox_drop},
+};
+
+#[repr(C)]
+struct Resource {
+  deleted: *const AtomicUsize,
+}
+
+/// What a C library would provide
+unsafe extern "C" fn resource_new(deleted: *const AtomicUsize) -> *mut c_void {
+  unsafe {
+    let res =
+      salloc::aligned_malloc(size_of::<Resource>(), align_of::<Resource>()) as *mut Resource;
+    res.write(Resource { deleted });
+
+    res as _
+  }
+}
+
+unsafe extern "C" fn resource_delete(data: *mut c_void, ctx: *mut c_void) {
+  unsafe {
+    let res = *(data as *mut *mut Resource);
+
+    (*(*res).deleted).fetch_add(1, Ordering::Relaxed);
+    (*(ctx as *const AtomicUsize)).fetch_add(1, Ordering::Relaxed);
+
+    salloc::aligned_free(res as _);
+  }
+}
+
+unsafe extern "C" fn count_delete(data: *mut c_void, _ctx: *mut c_void) {
+  unsafe { (**(data as *mut *const AtomicUsize)).fetch_add(1, Ordering::Relaxed) };
+}
+
+#[test]
+fn test_with_deleter() {
+  let deleted = AtomicUsize::new(0);
+
+  let boxed = unsafe { RTBox::with_deleter(&deleted as *const AtomicUsize, count_delete) }.unwrap();
+  assert_eq!(*boxed, &deleted as *const AtomicUsize);
+
+  // The value may only go through the deleter
+  let boxed = boxed.into_inner().err().unwrap();
+  assert_eq!(deleted.load(Ordering::Relaxed), 0);
+
+  drop(boxed);
+  assert_eq!(deleted.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_adopt() {
+  let deleted = AtomicUsize::new(0);
+  let ctx = AtomicUsize::new(0);
+
+  unsafe {
+    let res = resource_new(&deleted);
+    let data = saffi_rtbox_adopt(res, resource_delete, &ctx as *const _ as _);
+
+    let boxed = RTBox::<*mut c_void>::from_thin_raw(data).unwrap();
+    assert_eq!(*boxed, res);
+
+    drop(boxed);
+  }
+
+  assert_eq!(deleted.load(Ordering::Relaxed), 1);
+  assert_eq!(ctx.load(Ordering::Relaxed), 1);
+
+  // Dropped without knowing the type
+  let deleted = AtomicUsize::new(0);
+
+  unsafe {
+    let data = saffi_rtbox_adopt(
+      resource_new(&deleted),
+      resource_delete,
+      &ctx as *const _ as _,
+    );
+    saffi_rtbox_drop(data);
+
+    saffi_rtbox_drop(RTBox::new(5u64).unwrap().into_thin_raw());
+  }
+
+  assert_eq!(deleted.load(Ordering::Relaxed), 1);
+  assert_eq!(ctx.load(Ordering::Relaxed), 2);
+}
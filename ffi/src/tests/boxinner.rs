@@ -0,0 +1,120 @@
+use std::{
+  ffi::c_void,
+  ptr,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::{FfiTypeId, RTBox, RTBoxVTable, RTBoxWrapper, header_of},
+};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+static FOREIGN_FREES: AtomicUsize = AtomicUsize::new(0);
+static FOREIGN_DEALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(C)]
+struct Tracked(u64);
+
+unsafe impl FFISafe for Tracked {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl Drop for Tracked {
+  fn drop(&mut self) {
+    DROPS.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+// What another dylib would have put in the header, the offset is the one of u64
+const OFFSET: usize = size_of::<RTBoxWrapper>();
+
+unsafe extern "C" fn foreign_free(data: *mut c_void) {
+  FOREIGN_FREES.fetch_add(1, Ordering::Relaxed);
+
+  unsafe { salloc::aligned_free((data as *mut u8).sub(OFFSET) as _) };
+}
+
+unsafe extern "C" fn foreign_dealloc(data: *mut c_void) {
+  FOREIGN_DEALLOCS.fetch_add(1, Ordering::Relaxed);
+
+  unsafe { salloc::aligned_free((data as *mut u8).sub(OFFSET) as _) };
+}
+
+static FOREIGN: RTBoxVTable = RTBoxVTable {
+  free: foreign_free,
+  dealloc: Some(foreign_dealloc),
+  clone: None,
+  type_id: FfiTypeId::of::<u64>(),
+};
+
+static FOREIGN_NO_DEALLOC: RTBoxVTable = RTBoxVTable {
+  free: foreign_free,
+  dealloc: None,
+  clone: None,
+  type_id: FfiTypeId::of::<u64>(),
+};
+
+/// Builds the box like another dylib would, returning the thin pointer
+fn foreign_box(value: u64, vtable: &'static RTBoxVTable) -> *mut c_void {
+  unsafe {
+    let data = (salloc::aligned_malloc(OFFSET + 8, 8) as *mut u8).add(OFFSET);
+
+    ptr::write(header_of(data), RTBoxWrapper::new(0, vtable));
+    ptr::write(data as *mut u64, value);
+
+    data as _
+  }
+}
+
+#[test]
+fn test_into_inner() {
+  let before = DROPS.load(Ordering::Relaxed);
+
+  let boxed = RTBox::new(Tracked(5)).unwrap();
+  let value = boxed.into_inner().ok().unwrap();
+
+  // Moving out does not drop
+  assert_eq!(DROPS.load(Ordering::Relaxed), before);
+  assert_eq!(value.0, 5);
+
+  let nested = RTBox::new(RTBox::new(Tracked(6)).unwrap()).unwrap();
+  let inner = nested.into_inner().ok().unwrap();
+  assert_eq!(DROPS.load(Ordering::Relaxed), before);
+  assert_eq!(inner.0, 6);
+}
+
+#[test]
+fn test_take_replace() {
+  let mut boxed = RTBox::new(1u64).unwrap();
+
+  assert_eq!(boxed.replace(2), 1);
+  assert_eq!(boxed.take(), 2);
+  assert_eq!(*boxed, 0);
+}
+
+#[test]
+fn test_cross_dylib() {
+  let deallocs = FOREIGN_DEALLOCS.load(Ordering::Relaxed);
+  let frees = FOREIGN_FREES.load(Ordering::Relaxed);
+
+  let boxed = unsafe { RTBox::<u64>::from_thin_raw(foreign_box(7, &FOREIGN)) }.unwrap();
+  assert_eq!(boxed.into_inner().ok(), Some(7));
+
+  // Released by the allocating side, without dropping
+  assert_eq!(FOREIGN_DEALLOCS.load(Ordering::Relaxed), deallocs + 1);
+  assert_eq!(FOREIGN_FREES.load(Ordering::Relaxed), frees);
+
+  // No dealloc, the box is handed back and dropped through `free`
+  let boxed = unsafe { RTBox::<u64>::from_thin_raw(foreign_box(8, &FOREIGN_NO_DEALLOC)) }.unwrap();
+  let mut boxed = boxed.into_inner().err().unwrap();
+
+  assert_eq!(boxed.take(), 8);
+  drop(boxed);
+
+  assert_eq!(FOREIGN_FREES.load(Ordering::Relaxed), frees + 1);
+  assert_eq!(FOREIGN_DEALLOCS.load(Ordering::Relaxed), deallocs + 1);
+}
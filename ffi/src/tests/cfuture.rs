@@ -0,0 +1,112 @@
+use std::{
+  ffi::c_void,
+  mem::MaybeUninit,
+  sync::atomic::{AtomicUsize, Ordering},
+  thread,
+  time::Duration,
+};
+
+use crate::{
+  futures::{
+    CWaker, WakerVTable,
+    channel::oneshot,
+    foreign::{
+      CFuture, CFuturePoll, saffi_future_drop, saffi_future_output_layout, saffi_future_poll,
+    },
+    implements::{create_fallible_future, create_future},
+  },
+  string::str::SharableStr,
+};
+
+/// A waker of a C event loop, `data` points to a counter of wakeups
+unsafe extern "C" fn wake(waker: CWaker) {
+  unsafe { (*(waker.data() as *const AtomicUsize)).fetch_add(1, Ordering::SeqCst) };
+}
+
+unsafe extern "C" fn clone(waker: CWaker) -> CWaker {
+  waker
+}
+
+unsafe extern "C" fn free(_waker: CWaker) {}
+
+static LOOP_VTABLE: WakerVTable = WakerVTable {
+  wake_and_free: wake,
+  wake_no_free: wake,
+  waker_clone: clone,
+  free_waker: free,
+};
+
+/// Polls until the task is not pending anymore, sleeping until woken in between
+fn run<T>(task: *mut CFuture, out: &mut MaybeUninit<T>) -> CFuturePoll {
+  let wakeups = AtomicUsize::new(0);
+
+  loop {
+    let seen = wakeups.load(Ordering::SeqCst);
+
+    let status = unsafe {
+      saffi_future_poll(
+        task,
+        &wakeups as *const _ as _,
+        &LOOP_VTABLE,
+        out.as_mut_ptr() as *mut c_void,
+      )
+    };
+
+    if status != CFuturePoll::Pending {
+      return status;
+    }
+
+    while wakeups.load(Ordering::SeqCst) == seen {
+      thread::yield_now();
+    }
+  }
+}
+
+#[test]
+fn test_foreign_executor() {
+  let (tx, rx) = oneshot::channel::<u64>();
+
+  let task = create_future(async move { rx.await.unwrap() * 2 }).into_c_future();
+
+  let layout = unsafe { saffi_future_output_layout(task) };
+  assert!(layout.size >= size_of::<u64>());
+
+  thread::spawn(move || {
+    thread::sleep(Duration::from_millis(10));
+    tx.send(21).unwrap();
+  });
+
+  let mut out = MaybeUninit::<u64>::uninit();
+  assert_eq!(run(task, &mut out), CFuturePoll::Ready);
+  assert_eq!(unsafe { out.assume_init() }, 42);
+
+  assert_eq!(run(task, &mut out), CFuturePoll::ProtocolViolation);
+  unsafe { saffi_future_drop(task) };
+}
+
+#[test]
+fn test_foreign_executor_errors() {
+  let failed = create_fallible_future(async { Err::<u8, u32>(404) }).into_c_future();
+
+  let mut out = MaybeUninit::<u32>::uninit();
+  assert_eq!(run(failed, &mut out), CFuturePoll::Failed);
+  assert_eq!(unsafe { out.assume_init() }, 404);
+  unsafe { saffi_future_drop(failed) };
+
+  let panicked = create_future(async {
+    if true {
+      panic!("from the plugin");
+    }
+
+    0u8
+  })
+  .into_c_future();
+
+  let mut out = MaybeUninit::<SharableStr>::uninit();
+  assert_eq!(run(panicked, &mut out), CFuturePoll::Panicked);
+  assert_eq!(&*unsafe { out.assume_init() }, "from the plugin");
+  unsafe { saffi_future_drop(panicked) };
+
+  // Never polled, so it is aborted
+  unsafe { saffi_future_drop(create_future(async { 1u8 }).into_c_future()) };
+}
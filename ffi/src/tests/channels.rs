@@ -0,0 +1,130 @@
+use std::{future::poll_fn, pin::Pin, task::Poll};
+
+use futures_sink::Sink;
+
+use crate::futures::channel::{
+  Closed, TryRecvError, TrySendError,
+  mpsc::{self, FfiSink},
+  oneshot, watch,
+};
+
+/// Stands in for a function exported by a plugin, the halves cross the boundary by value
+extern "C" fn plugin_sum(mut rx: mpsc::Receiver<u32>, tx: oneshot::Sender<u32>) {
+  tokio::spawn(async move {
+    let mut sum = 0;
+
+    while let Some(value) = rx.recv().await {
+      sum += value;
+    }
+
+    _ = tx.send(sum);
+  });
+}
+
+#[tokio::test]
+async fn test_mpsc() {
+  let (tx, rx) = mpsc::channel::<u32>(2);
+  let (sum_tx, sum_rx) = oneshot::channel();
+
+  plugin_sum(rx, sum_tx);
+
+  let producers = (0..4).map(|p| {
+    let tx = tx.clone();
+
+    tokio::spawn(async move {
+      for i in 0..100 {
+        tx.send(p * 100 + i).await.unwrap();
+      }
+    })
+  });
+
+  for producer in producers.collect::<Vec<_>>() {
+    producer.await.unwrap();
+  }
+  drop(tx);
+
+  assert_eq!(sum_rx.await, Ok((0..400).sum()));
+}
+
+#[tokio::test]
+async fn test_mpsc_backpressure() {
+  let (tx, mut rx) = mpsc::channel::<u8>(2);
+
+  tx.try_send(1).unwrap();
+  tx.try_send(2).unwrap();
+  assert!(matches!(tx.try_send(3), Err(TrySendError::Full(3))));
+
+  let mut sink = FfiSink::new(tx.clone());
+  let ready = poll_fn(|cx| Poll::Ready(Pin::new(&mut sink).poll_ready(cx))).await;
+  assert!(ready.is_pending());
+
+  assert_eq!(rx.recv().await, Some(1));
+
+  poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx))
+    .await
+    .unwrap();
+  // The slot is reserved for the sink
+  assert!(matches!(tx.try_send(4), Err(TrySendError::Full(4))));
+  Pin::new(&mut sink).start_send(5).unwrap();
+
+  assert_eq!(rx.try_recv(), Ok(2));
+  assert_eq!(rx.try_recv(), Ok(5));
+  assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+  drop((tx, sink));
+  assert_eq!(rx.recv().await, None);
+
+  let (tx, rx) = mpsc::channel::<u8>(1);
+  drop(rx);
+  assert!(tx.is_closed());
+  assert!(matches!(tx.try_send(1), Err(TrySendError::Closed(1))));
+  assert!(tx.send(2).await.is_err());
+}
+
+#[tokio::test]
+async fn test_oneshot() {
+  let (tx, rx) = oneshot::channel::<u64>();
+
+  tokio::spawn(async move {
+    tokio::task::yield_now().await;
+
+    tx.send(7).unwrap();
+  });
+  assert_eq!(rx.await, Ok(7));
+
+  let (tx, rx) = oneshot::channel::<u64>();
+  drop(tx);
+  assert_eq!(rx.await, Err(Closed));
+
+  let (tx, rx) = oneshot::channel::<u64>();
+  drop(rx);
+  assert_eq!(tx.send(8), Err(8));
+}
+
+#[tokio::test]
+async fn test_watch() {
+  let (tx, mut rx) = watch::channel::<u32>(0);
+  let mut other = tx.subscribe();
+
+  assert!(!rx.has_changed());
+
+  let waiter = tokio::spawn(async move {
+    other.changed().await.unwrap();
+
+    *other.borrow_and_update()
+  });
+
+  tokio::task::yield_now().await;
+  tx.send(1).unwrap();
+  assert_eq!(tx.send_replace(2), 1);
+
+  let seen = waiter.await.unwrap();
+  assert!(seen == 1 || seen == 2);
+
+  rx.changed().await.unwrap();
+  assert_eq!(*rx.borrow(), 2);
+  assert!(!rx.has_changed());
+
+  drop(tx);
+  assert_eq!(rx.changed().await, Err(Closed));
+}
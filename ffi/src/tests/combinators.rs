@@ -0,0 +1,66 @@
+use std::{future::pending, time::Duration};
+
+use crate::futures::{
+  FFIFuture, FutureError, FutureTask,
+  combinators::Either,
+  implements::{create_fallible_future, create_future},
+};
+
+async fn after(ms: u64, value: u32) -> u32 {
+  tokio::time::sleep(Duration::from_millis(ms)).await;
+
+  value
+}
+
+#[tokio::test]
+async fn test_join_all() {
+  let tasks = (0..10u32).map(|i| create_future(after(10 - i as u64, i)));
+  let out = FFIFuture::new(FutureTask::join_all(tasks)).await.unwrap();
+
+  assert_eq!(&*out, &(0..10).collect::<Vec<_>>()[..]);
+
+  let failing = [
+    create_fallible_future(async { Ok::<u32, u8>(after(5, 1).await) }),
+    create_fallible_future(async { Err::<u32, u8>(2) }),
+    create_fallible_future(pending()),
+  ];
+  assert!(matches!(
+    FFIFuture::new(FutureTask::join_all(failing)).await,
+    Err(FutureError::Failed(2))
+  ));
+}
+
+#[tokio::test]
+async fn test_select_race() {
+  let slow = create_future(after(50, 1));
+  let fast = create_future(async { after(1, 2).await as u8 });
+  assert_eq!(
+    FFIFuture::new(slow.select(fast)).await,
+    Ok(Either::Right(2))
+  );
+
+  let tasks = [
+    create_future(after(50, 1)),
+    create_future(after(1, 2)),
+    create_future(pending()),
+  ];
+  assert_eq!(FFIFuture::new(FutureTask::race(tasks)).await, Ok(2));
+}
+
+#[tokio::test]
+async fn test_map() {
+  let task = create_future(after(1, 20)).map(|out| out as u64 * 2);
+  assert_eq!(FFIFuture::new(task).await, Ok(40));
+
+  let panicking = create_future(async {
+    if true {
+      panic!("child panicked");
+    }
+
+    0u32
+  })
+  .map(|out| out + 1);
+
+  let err = FFIFuture::new(panicking).await.unwrap_err();
+  assert_eq!(err.panic_message(), Some("child panicked"));
+}
@@ -0,0 +1,100 @@
+use std::{
+  ffi::c_void,
+  sync::atomic::{AtomicU64, Ordering},
+  thread,
+  time::{Duration, Instant},
+};
+
+use crate::futures::{
+  MaybeData, Result, channel::oneshot, completion::drive_completions,
+  implements::create_fallible_future,
+};
+
+/// Stands in for a C host, `ctx` points to an `AtomicU64` set to the output + 1
+extern "C" fn on_output(ctx: *mut c_void, output: Result<u64, u64>) {
+  let slot = unsafe { &*(ctx as *const AtomicU64) };
+
+  let value = match output.into_data() {
+    MaybeData::Some(x) => x + 1,
+    MaybeData::Err(e) => e + 1_000,
+    _ => u64::MAX,
+  };
+
+  slot.store(value, Ordering::Release);
+}
+
+fn wait_for(slot: &AtomicU64, mut drive: impl FnMut()) -> u64 {
+  let start = Instant::now();
+
+  loop {
+    drive();
+
+    let value = slot.load(Ordering::Acquire);
+    if value != 0 {
+      return value;
+    }
+
+    assert!(start.elapsed() < Duration::from_secs(10), "never completed");
+    thread::yield_now();
+  }
+}
+
+#[test]
+fn test_completion_driven_manually() {
+  let (tx, rx) = oneshot::channel::<u64>();
+
+  let task = create_fallible_future(async move { rx.await.map_err(|_| 0u64) });
+
+  let slot = AtomicU64::new(0);
+  task.on_complete(on_output, &slot as *const _ as _);
+
+  // Pending until another thread sends
+  drive_completions();
+  assert_eq!(slot.load(Ordering::Acquire), 0);
+
+  thread::spawn(move || {
+    thread::sleep(Duration::from_millis(10));
+    tx.send(41).unwrap();
+  });
+
+  assert_eq!(
+    wait_for(&slot, || {
+      drive_completions();
+    }),
+    42
+  );
+
+  let failed = create_fallible_future(async { Err::<u64, u64>(7) });
+  let slot = AtomicU64::new(0);
+  failed.on_complete(on_output, &slot as *const _ as _);
+
+  assert_eq!(
+    wait_for(&slot, || {
+      drive_completions();
+    }),
+    1_007
+  );
+}
+
+#[test]
+fn test_completion_driven_by_savmasync() {
+  unsafe { savmasync::register(200, drive_completions) };
+
+  let slot = AtomicU64::new(0);
+  let task = create_fallible_future(async {
+    let mut sum = 0;
+
+    for i in 0..10 {
+      let (tx, rx) = oneshot::channel::<u64>();
+      thread::spawn(move || tx.send(i).unwrap());
+      sum += rx.await.unwrap();
+    }
+
+    Ok::<u64, u64>(sum)
+  });
+  task.on_complete(on_output, &slot as *const _ as _);
+
+  assert_eq!(wait_for(&slot, || {}), 46);
+
+  unsafe { savmasync::unregister(200, drive_completions) };
+}
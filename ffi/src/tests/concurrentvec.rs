@@ -0,0 +1,66 @@
+use std::{sync::Arc, thread};
+
+use crate::{boxed::RTBox, vector::concurrent::FfiConcurrentVec};
+
+#[test]
+fn test_single_thread() {
+  let vec = FfiConcurrentVec::<u64>::new();
+
+  for i in 0..1000 {
+    assert_eq!(vec.push(i), i as usize);
+  }
+
+  let snapshot = vec.snapshot();
+  assert_eq!(snapshot.len(), 1000);
+
+  // Addresses are stable while pushing
+  let first = snapshot.get(0).unwrap() as *const u64;
+  vec.push(1000);
+  assert_eq!(vec.get(0).unwrap() as *const u64, first);
+
+  assert_eq!(snapshot.len(), 1000);
+  assert_eq!(snapshot.iter().sum::<u64>(), (0..1000).sum());
+  assert_eq!(vec.get(1000), Some(&1000));
+}
+
+#[test]
+fn test_many_writers() {
+  const THREADS: u64 = 8;
+  const PER_THREAD: u64 = 5000;
+
+  let vec = Arc::new(FfiConcurrentVec::<RTBox<u64>>::new());
+
+  let handles = (0..THREADS)
+    .map(|t| {
+      let vec = vec.clone();
+
+      thread::spawn(move || {
+        for i in 0..PER_THREAD {
+          vec.push(RTBox::new(t * PER_THREAD + i).unwrap());
+
+          // Readers only ever see completely written elements
+          let snapshot = vec.snapshot();
+          if let Some(last) = snapshot.len().checked_sub(1) {
+            assert!(**snapshot.get(last).unwrap() < THREADS * PER_THREAD);
+          }
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+
+  for handle in handles {
+    handle.join().unwrap();
+  }
+
+  let mut vec = Arc::into_inner(vec).unwrap();
+
+  let mut values = vec.snapshot().iter().map(|x| **x).collect::<Vec<_>>();
+  values.sort();
+  assert_eq!(values, (0..THREADS * PER_THREAD).collect::<Vec<_>>());
+
+  vec.clear();
+  assert!(vec.is_empty());
+
+  vec.push(RTBox::new(1).unwrap());
+  assert_eq!(vec.len(), 1);
+}
@@ -0,0 +1,58 @@
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::{FfiAny, FfiTypeId, RTBox},
+};
+
+#[repr(C)]
+struct Point {
+  x: i32,
+  y: i32,
+}
+
+unsafe impl FFISafe for Point {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+#[test]
+fn test_type_id() {
+  assert_eq!(FfiTypeId::of::<Point>(), FfiTypeId::of::<Point>());
+  assert_ne!(FfiTypeId::of::<u32>(), FfiTypeId::of::<i32>());
+  assert_ne!(FfiTypeId::of::<u8>(), FfiTypeId::of_slice::<u8>());
+  assert_eq!(
+    FfiTypeId::of::<Point>().name(),
+    std::any::type_name::<Point>()
+  );
+
+  // Usable in constants, which is what puts them in the vtables
+  const ID: FfiTypeId = FfiTypeId::of::<Point>();
+  assert_eq!(ID, FfiTypeId::of::<Point>());
+}
+
+#[test]
+fn test_downcast() {
+  let mut any = FfiAny::new(Point { x: 1, y: 2 }).unwrap();
+
+  assert!(any.is::<Point>());
+
+  assert!(any.downcast_ref::<u64>().is_none());
+  assert_eq!(any.downcast_ref::<Point>().unwrap().y, 2);
+
+  any.downcast_mut::<Point>().unwrap().x = 10;
+
+  let any = any.downcast::<u64>().err().unwrap();
+  let point = any.downcast::<Point>().ok().unwrap();
+  assert_eq!(point.x, 10);
+}
+
+#[test]
+fn test_through_pointer() {
+  // What the host would receive from a plugin
+  let raw = FfiAny::from(RTBox::new(5u64).unwrap()).into_raw();
+
+  let any = unsafe { FfiAny::from_raw(raw) }.unwrap();
+  assert_eq!(any.type_id(), FfiTypeId::of::<u64>());
+  assert_eq!(any.downcast::<u64>().ok().map(|x| *x), Some(5));
+}
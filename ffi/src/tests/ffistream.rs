@@ -0,0 +1,127 @@
+use std::{
+  future::poll_fn,
+  pin::Pin,
+  sync::atomic::{AtomicUsize, Ordering},
+  task::{Context, Poll},
+};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::futures::{
+  FutureError,
+  implements::{create_fallible_stream, create_stream},
+  stream::FFIStream,
+};
+
+/// Yields `0..end`, returning `Pending` once before every item
+struct Counter {
+  next: u32,
+  end: u32,
+  yielded: bool,
+}
+
+impl Counter {
+  fn new(end: u32) -> Self {
+    Self {
+      next: 0,
+      end,
+      yielded: false,
+    }
+  }
+}
+
+impl Stream for Counter {
+  type Item = u32;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u32>> {
+    if !self.yielded {
+      self.yielded = true;
+      cx.waker().wake_by_ref();
+
+      return Poll::Pending;
+    }
+
+    self.yielded = false;
+
+    if self.next == self.end {
+      return Poll::Ready(None);
+    }
+
+    if self.next == 13 {
+      panic!("unlucky");
+    }
+
+    self.next += 1;
+
+    Poll::Ready(Some(self.next - 1))
+  }
+}
+
+static STREAM_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+impl Drop for Counter {
+  fn drop(&mut self) {
+    STREAM_DROPS.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+  poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[tokio::test]
+async fn test_stream() {
+  let mut stream = FFIStream::new(create_stream(Counter::new(3)));
+
+  for i in 0..3 {
+    assert_eq!(next(&mut stream).await, Some(Ok(i)));
+  }
+
+  assert_eq!(next(&mut stream).await, None);
+  assert!(stream.is_terminated());
+  assert_eq!(next(&mut stream).await, None);
+}
+
+struct Evens(Counter);
+
+impl Stream for Evens {
+  type Item = Result<u32, u32>;
+
+  fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    Pin::new(&mut self.0)
+      .poll_next(cx)
+      .map(|item| item.map(|i| if i % 2 == 0 { Ok(i) } else { Err(i) }))
+  }
+}
+
+#[tokio::test]
+async fn test_stream_errors() {
+  let mut stream = FFIStream::new(create_fallible_stream(Evens(Counter::new(3))));
+
+  assert_eq!(next(&mut stream).await, Some(Ok(0)));
+  assert_eq!(next(&mut stream).await, Some(Err(FutureError::Failed(1))));
+  assert_eq!(next(&mut stream).await, Some(Ok(2)));
+  assert_eq!(next(&mut stream).await, None);
+}
+
+#[tokio::test]
+async fn test_stream_panic_and_abort() {
+  let mut stream = FFIStream::new(create_stream(Counter::new(20)));
+
+  for i in 0..13 {
+    assert_eq!(next(&mut stream).await, Some(Ok(i)));
+  }
+
+  let err = next(&mut stream).await.unwrap().unwrap_err();
+  assert_eq!(err.panic_message(), Some("unlucky"));
+  assert_eq!(next(&mut stream).await, None);
+
+  let before = STREAM_DROPS.load(Ordering::SeqCst);
+
+  let mut stream = FFIStream::new(create_stream(Counter::new(5)));
+  assert_eq!(next(&mut stream).await, Some(Ok(0)));
+  drop(stream);
+
+  drop(create_stream(Counter::new(5)));
+  assert!(STREAM_DROPS.load(Ordering::SeqCst) >= before + 2);
+}
@@ -0,0 +1,122 @@
+use std::{
+  future::poll_fn,
+  pin::pin,
+  task::{Context, Poll, Waker},
+};
+
+use crate::futures::{
+  CBReason, CWaker, FFIFuture, FutureError, FutureTask, MaybeData, Result, State, WakerVTable,
+  implements::{create_fallible_future, create_future},
+};
+
+#[tokio::test]
+async fn test_error_channel() {
+  let ok = create_fallible_future(async { Ok::<u32, u16>(1) });
+  assert_eq!(FFIFuture::new(ok).await, Ok(1));
+
+  let failed = create_fallible_future(async {
+    tokio::task::yield_now().await;
+
+    Err::<u32, u16>(404)
+  });
+  assert_eq!(FFIFuture::new(failed).await, Err(FutureError::Failed(404)));
+
+  assert_eq!(FFIFuture::new(create_future(async { 2u8 })).await, Ok(2));
+}
+
+#[test]
+#[should_panic(expected = "polled after completion")]
+fn test_poll_after_completion() {
+  let mut fut = pin!(FFIFuture::new(create_future(async { 3u8 })));
+  let mut cx = Context::from_waker(Waker::noop());
+
+  assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(3)));
+  let _ = fut.as_mut().poll(&mut cx);
+}
+
+unsafe extern "C" fn noop_waker(_waker: CWaker) {}
+
+unsafe extern "C" fn noop_clone(waker: CWaker) -> CWaker {
+  waker
+}
+
+static NOOP_VTABLE: WakerVTable = WakerVTable {
+  wake_and_free: noop_waker,
+  wake_no_free: noop_waker,
+  waker_clone: noop_clone,
+  free_waker: noop_waker,
+};
+
+#[test]
+fn test_collect_after_completion() {
+  let mut task = create_future(async { 4u8 });
+
+  task.with_raw(|state, cb| {
+    cb(
+      state,
+      CBReason::SealWakerVTable {
+        vtable: &NOOP_VTABLE,
+      },
+    );
+
+    // The future is dropped once ready, later polls only see it was collected
+    assert!(matches!(
+      cb(state, CBReason::PollCollect).into_data(),
+      MaybeData::Some(4)
+    ));
+    assert!(cb(state, CBReason::PollCollect).is_collected());
+  });
+}
+
+/// A foreign callback that claims it was collected before it ever completed
+extern "C" fn broken_cb(_state: State, _action: CBReason) -> Result<u8, u8> {
+  Result::collected()
+}
+
+#[tokio::test]
+#[should_panic(expected = "PROTOCOL VIOLATION")]
+async fn test_protocol_violation() {
+  let task = unsafe { FutureTask::from_raw(1 as State, broken_cb) }.unwrap();
+  let mut fut = pin!(FFIFuture::new(task));
+
+  let _ = poll_fn(|cx| fut.as_mut().poll(cx)).await;
+}
+
+struct Loud;
+
+impl Drop for Loud {
+  fn drop(&mut self) {
+    panic!("dropped loudly");
+  }
+}
+
+#[tokio::test]
+async fn test_panic_is_delivered() {
+  let panicking = create_future(async {
+    tokio::task::yield_now().await;
+
+    if true {
+      panic!("plugin broke: {}", 42);
+    }
+
+    0u8
+  });
+
+  let err = FFIFuture::new(panicking).await.unwrap_err();
+  assert_eq!(err.panic_message(), Some("plugin broke: 42"));
+
+  let resumed = std::panic::catch_unwind(move || err.into_failed());
+  let payload = resumed.unwrap_err();
+  assert_eq!(
+    payload.downcast_ref::<String>().unwrap(),
+    "plugin broke: 42"
+  );
+
+  // A panic while aborting is swallowed instead of unwinding into the host
+  let loud = Loud;
+  drop(create_future(async move {
+    let _loud = loud;
+
+    1u8
+  }));
+}
@@ -0,0 +1,113 @@
+use std::{
+  ffi::c_void,
+  panic::{AssertUnwindSafe, catch_unwind},
+  sync::Arc,
+  thread,
+};
+
+use crate::handle::{
+  Handle, HandleTable, saffi_handle_get, saffi_handle_insert, saffi_handle_is_valid,
+  saffi_handle_remove, saffi_handle_table_free, saffi_handle_table_new,
+};
+
+#[test]
+fn test_generations() {
+  let table = HandleTable::new();
+
+  let a = table.insert(1u64);
+  let b = table.insert(2u64);
+  assert_ne!(a.to_raw(), 0);
+  assert_eq!(table.get(b), Some(2));
+
+  assert_eq!(table.remove(a), Some(1));
+  assert!(!table.contains(a));
+  assert_eq!(table.remove(a), None);
+
+  // The slot is reused, but the old handle stays dead
+  let c = table.insert(3u64);
+  assert_eq!(c.index(), a.index());
+  assert_ne!(c, a);
+  assert_eq!(table.get(a), None);
+  assert_eq!(table.get(c), Some(3));
+
+  table.with_mut(c, |x| *x += 1);
+  assert_eq!(table.with(c, |x| *x), Some(4));
+  assert_eq!(table.len(), 2);
+
+  assert!(!table.contains(Handle::NULL));
+  assert!(!table.contains(Handle::from_raw(u64::MAX)));
+}
+
+#[test]
+fn test_threads() {
+  let table = Arc::new(HandleTable::new());
+
+  let threads = (0..4)
+    .map(|t| {
+      let table = table.clone();
+
+      thread::spawn(move || {
+        for i in 0..1000u64 {
+          let handle = table.insert(t * 1000 + i);
+          assert_eq!(table.get(handle), Some(t * 1000 + i));
+
+          if i % 2 == 0 {
+            assert_eq!(table.remove(handle), Some(t * 1000 + i));
+          }
+        }
+      })
+    })
+    .collect::<Vec<_>>();
+
+  for t in threads {
+    t.join().unwrap();
+  }
+
+  assert_eq!(table.len(), 2000);
+}
+
+#[test]
+fn test_c_api() {
+  let mut value = 5u32;
+  let ptr = &mut value as *mut u32 as *mut c_void;
+
+  unsafe {
+    let table = saffi_handle_table_new();
+
+    let handle = saffi_handle_insert(table, ptr);
+    assert!(saffi_handle_is_valid(table, handle));
+    assert_eq!(saffi_handle_get(table, handle), ptr);
+
+    assert_eq!(saffi_handle_remove(table, handle), ptr);
+    assert!(!saffi_handle_is_valid(table, handle));
+    assert!(saffi_handle_get(table, handle).is_null());
+
+    saffi_handle_table_free(table);
+  }
+}
+
+#[test]
+fn test_poisoned() {
+  let mut value = 5u32;
+  let ptr = &mut value as *mut u32 as *mut c_void;
+
+  unsafe {
+    let table = saffi_handle_table_new();
+    let handle = saffi_handle_insert(table, ptr);
+
+    // Poisons the lock
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+      (*table).with_mut(Handle::from_raw(handle), |_| panic!("poison"))
+    }));
+
+    assert!(saffi_handle_is_valid(table, handle));
+    assert_eq!(saffi_handle_get(table, handle), ptr);
+
+    let other = saffi_handle_insert(table, ptr);
+    assert_ne!(other, 0);
+    assert_eq!((*table).len(), 2);
+
+    assert_eq!(saffi_handle_remove(table, handle), ptr);
+    saffi_handle_table_free(table);
+  }
+}
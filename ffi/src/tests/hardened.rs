@@ -0,0 +1,76 @@
+use std::mem::ManuallyDrop;
+
+use crate::{
+  boxed::RTBox,
+  string::str::SharableStr,
+  vector::{Vector, deque::FfiVecDeque},
+};
+
+#[test]
+fn test_conversions_retag() {
+  let mut bytes = Vector::new();
+  bytes.extend_from_slice(b"tagged");
+
+  let string = SharableStr::from_utf8(bytes).ok().unwrap();
+  assert_eq!(&*string, "tagged");
+
+  let bytes = string.into_bytes();
+  let boxed = RTBox::<[u8]>::from(bytes);
+  assert_eq!(&*boxed, b"tagged");
+}
+
+#[test]
+#[should_panic(expected = "expected the header of a Vector but found the one of a RTBox")]
+fn test_wrong_container() {
+  let boxed = RTBox::new(5u64).unwrap();
+
+  let _ = unsafe { Vector::from_raw(boxed.into_raw()) };
+}
+
+#[test]
+#[should_panic(expected = "expected the header of a SharableStr but found the one of a Vector")]
+fn test_vector_as_str() {
+  let mut bytes = Vector::<u8>::new();
+  bytes.push(b'a');
+
+  let _ = unsafe { SharableStr::from_raw(bytes.into_raw()) };
+}
+
+#[test]
+#[should_panic(expected = "has a corrupted header")]
+fn test_corrupted() {
+  let string = ManuallyDrop::new(SharableStr::create("overrun"));
+
+  unsafe {
+    // A buggy plugin writing in front of the string
+    let base = (string.as_ptr() as *mut u8).sub(32);
+    base.write_bytes(0x41, 8);
+  }
+
+  let _ = string.len();
+}
+
+#[test]
+#[should_panic(expected = "known_len is 5 but the header says 3")]
+fn test_known_len() {
+  let mut vec = Vector::new();
+  vec.extend([1u32, 2, 3]);
+
+  let _ = unsafe { vec.get_aided(Some(5), 0) };
+}
+
+#[test]
+fn test_full_deque_round_trip() {
+  let mut deque = FfiVecDeque::<u64>::new();
+  deque.push_back(2);
+  deque.push_front(1);
+  assert_eq!(deque.len(), deque.cap());
+
+  let vector = Vector::from(deque);
+  assert_eq!(&*vector, &[1, 2]);
+  assert_eq!(vector.len(), vector.cap());
+
+  let deque = FfiVecDeque::from(vector);
+  assert_eq!(deque.as_slices(), (&[1, 2][..], &[][..]));
+  assert_eq!(deque.len(), deque.cap());
+}
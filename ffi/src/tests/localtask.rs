@@ -0,0 +1,97 @@
+use std::{
+  cell::Cell,
+  future::poll_fn,
+  pin::Pin,
+  rc::Rc,
+  task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::futures::{
+  FFIFuture,
+  implements::{
+    create_future, create_local_fallible_future, create_local_future, create_local_stream,
+    create_stream,
+  },
+  local::{LocalFFIFuture, LocalFFIStream},
+  stream::FFIStream,
+};
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[tokio::test]
+async fn test_send_task() {
+  let fut = FFIFuture::new(create_future(async { 1u8 }));
+  assert_send(&fut);
+
+  // Moved across worker threads
+  assert_eq!(tokio::spawn(fut).await.unwrap(), Ok(1));
+}
+
+#[tokio::test]
+async fn test_local_task() {
+  let counter = Rc::new(Cell::new(0u32));
+
+  let shared = counter.clone();
+  let task = create_local_future(async move {
+    tokio::task::yield_now().await;
+    shared.set(shared.get() + 1);
+
+    shared.get()
+  });
+
+  assert_eq!(LocalFFIFuture::new(task).await, Ok(1));
+  assert_eq!(counter.get(), 1);
+
+  let rc = Rc::new(5u16);
+  let failed = create_local_fallible_future(async move { Err::<u8, u16>(*rc) });
+  assert!(LocalFFIFuture::new(failed).await.is_err());
+}
+
+#[test]
+fn test_local_task_drop() {
+  let counter = Rc::new(());
+
+  let shared = counter.clone();
+  drop(create_local_future(async move { drop(shared) }));
+
+  assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+/// Counts down from the shared counter
+struct Countdown<C>(C);
+
+impl<C: core::ops::Deref<Target = Cell<u32>> + Unpin> Stream for Countdown<C> {
+  type Item = u32;
+
+  fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<u32>> {
+    let left = self.0.get();
+
+    if left == 0 {
+      return Poll::Ready(None);
+    }
+
+    self.0.set(left - 1);
+    Poll::Ready(Some(left))
+  }
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+  poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[tokio::test]
+async fn test_local_stream() {
+  let counter = Rc::new(Cell::new(2));
+  let mut stream = LocalFFIStream::new(create_local_stream(Countdown(counter.clone())));
+
+  assert_eq!(next(&mut stream).await, Some(Ok(2)));
+  assert_eq!(next(&mut stream).await, Some(Ok(1)));
+  assert_eq!(next(&mut stream).await, None);
+  assert_eq!(counter.get(), 0);
+
+  let mut sent = FFIStream::new(create_stream(Countdown(Box::new(Cell::new(1)))));
+  assert_send(&sent);
+  assert_eq!(next(&mut sent).await, Some(Ok(1)));
+}
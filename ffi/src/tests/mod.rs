@@ -1 +1,27 @@
 pub mod atomicffiwaker;
+pub mod boxclone;
+pub mod boxdeleter;
+pub mod boxinner;
+pub mod cfuture;
+pub mod channels;
+pub mod combinators;
+pub mod completion;
+pub mod concurrentvec;
+pub mod ffiany;
+pub mod ffistream;
+pub mod futureerror;
+pub mod handletable;
+#[cfg(feature = "hardened")]
+pub mod hardened;
+pub mod localtask;
+pub mod ownership;
+pub mod pool;
+pub mod promise;
+pub mod rawvector;
+pub mod sharablestr;
+pub mod smallvec;
+pub mod uninitbox;
+pub mod unsizedbox;
+pub mod vecdeque;
+pub mod vector;
+pub mod vectorio;
@@ -0,0 +1,166 @@
+use std::{
+  alloc::Layout,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+  boxed::{FfiAny, RTBox},
+  futures::{FFIFuture, FutureTask, implements::create_future},
+  string::str::SharableStr,
+  vector::{Vector, deque::FfiVecDeque, raw::RawVector},
+};
+
+#[test]
+fn test_round_trip() {
+  let boxed = RTBox::new(7u64).unwrap();
+  let raw = boxed.into_raw();
+  assert_eq!(*unsafe { RTBox::from_raw(raw) }.unwrap(), 7);
+
+  let mut vector = Vector::<u32>::new();
+  vector.extend([1, 2, 3]);
+  let raw = vector.into_raw();
+  assert_eq!(&*unsafe { Vector::from_raw(raw) }.unwrap(), &[1, 2, 3]);
+
+  let string = SharableStr::create("owned");
+  let raw = string.into_raw();
+  assert_eq!(&*unsafe { SharableStr::from_raw(raw) }.unwrap(), "owned");
+
+  let mut deque = FfiVecDeque::<u32>::new();
+  deque.push_back(2);
+  deque.push_front(1);
+  let raw = deque.into_raw();
+  let vector: Vector<u32> = unsafe { FfiVecDeque::from_raw(raw) }.unwrap().into();
+  assert_eq!(&*vector, &[1, 2]);
+
+  let any = FfiAny::new(9u16).unwrap();
+  let raw = any.into_raw();
+  let any = unsafe { FfiAny::from_raw(raw) }.unwrap();
+  assert_eq!(any.downcast_ref::<u16>(), Some(&9));
+
+  let mut vector = Vector::<u32>::new();
+  vector.extend([5, 6]);
+  let raw = RawVector::from(vector);
+  let (layout, drop_fn) = (raw.elem_layout(), raw.drop_fn());
+  let ptr = raw.into_raw();
+  let raw = unsafe { RawVector::from_raw(ptr, layout, drop_fn) }.unwrap();
+  assert_eq!(raw.get(1), Some(&6u32.to_ne_bytes()[..]));
+}
+
+#[test]
+fn test_from_null() {
+  unsafe {
+    assert!(RTBox::<u64>::from_raw(std::ptr::null_mut()).is_none());
+    assert!(Vector::<u64>::from_raw(std::ptr::null_mut()).is_none());
+    assert!(SharableStr::from_raw(std::ptr::null_mut()).is_none());
+    assert!(FfiVecDeque::<u64>::from_raw(std::ptr::null_mut()).is_none());
+    assert!(FfiAny::from_raw(std::ptr::null_mut()).is_none());
+    assert!(RawVector::from_raw(std::ptr::null_mut(), Layout::new::<u64>(), None).is_none());
+  }
+}
+
+#[test]
+fn test_raw_is_stable() {
+  let mut boxed = RTBox::new(1u64).unwrap();
+  let ptr = boxed.as_raw();
+  boxed.with_raw(|raw| unsafe { *raw = 2 });
+  assert_eq!(boxed.into_raw() as *const u64, ptr);
+  drop(unsafe { RTBox::from_raw(ptr as *mut u64) });
+
+  let mut vector = Vector::<u8>::new();
+  vector.extend([1, 2]);
+  let ptr = vector.as_raw();
+  vector.with_raw(|raw| unsafe { *raw.add(1) = 3 });
+  assert_eq!(&*vector, &[1, 3]);
+  assert_eq!(vector.into_raw() as *const u8, ptr);
+  drop(unsafe { Vector::from_raw(ptr as *mut u8) });
+
+  let mut string = SharableStr::create("abc");
+  let ptr = string.as_raw();
+  assert_eq!(string.with_raw(|raw| unsafe { *raw }), b'a');
+  assert_eq!(string.into_raw() as *const u8, ptr);
+  drop(unsafe { SharableStr::from_raw(ptr as *mut u8) });
+
+  let mut raw = RawVector::of::<u16>();
+  unsafe { raw.push(&1u16.to_ne_bytes()) };
+  let ptr = raw.as_raw();
+  raw.with_raw(|raw| unsafe { *(raw as *mut u16) = 7 });
+  assert_eq!(raw.get(0), Some(&7u16.to_ne_bytes()[..]));
+  assert_eq!(raw.into_raw() as *const u8, ptr);
+  drop(unsafe { RawVector::from_raw(ptr as *mut u8, Layout::new::<u16>(), None) });
+}
+
+#[test]
+fn test_leak() {
+  let leaked: &'static mut u64 = RTBox::new(3u64).unwrap().leak();
+  *leaked += 1;
+  assert_eq!(*leaked, 4);
+
+  let mut vector = Vector::<u8>::new();
+  vector.extend([4, 5]);
+  assert_eq!(vector.leak(), &[4, 5]);
+
+  assert_eq!(SharableStr::create("leaked").leak(), "leaked");
+
+  let mut deque = FfiVecDeque::<u8>::new();
+  deque.push_back(2);
+  deque.push_front(1);
+  assert_eq!(deque.leak(), &[1, 2]);
+
+  let mut raw = RawVector::of::<u8>();
+  unsafe { raw.push(&[8]) };
+  assert_eq!(unsafe { raw.leak().as_ref() }, &[8]);
+}
+
+static FUTURE_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Counted;
+
+impl Drop for Counted {
+  fn drop(&mut self) {
+    FUTURE_DROPS.fetch_add(1, Ordering::SeqCst);
+  }
+}
+
+fn counted_task() -> FutureTask<u64> {
+  let guard = Counted;
+
+  create_future(async move {
+    let _guard = guard;
+
+    5u64
+  })
+}
+
+#[tokio::test]
+async fn test_future_task_drop() {
+  let before = FUTURE_DROPS.load(Ordering::SeqCst);
+
+  drop(counted_task());
+  assert_eq!(FUTURE_DROPS.load(Ordering::SeqCst), before + 1);
+
+  let (state, cb) = counted_task().into_raw();
+  let task = unsafe { FutureTask::from_raw(state, cb) }.unwrap();
+  assert_eq!(task.as_raw(), state);
+  assert_eq!(FFIFuture::new(task).await, Ok(5));
+  assert_eq!(FUTURE_DROPS.load(Ordering::SeqCst), before + 2);
+
+  drop(FFIFuture::new(counted_task()));
+  assert_eq!(FUTURE_DROPS.load(Ordering::SeqCst), before + 3);
+}
+
+#[tokio::test]
+async fn test_abort_pinned_future() {
+  let mut fut = Box::pin(FFIFuture::new(create_future(async {
+    tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+
+    1u8
+  })));
+
+  // Registers the sleep into the timer wheel, which must be unlinked by the abort
+  assert!(poll_once(fut.as_mut()).await.is_pending());
+  drop(fut);
+}
+
+async fn poll_once<F: Future>(mut fut: std::pin::Pin<&mut F>) -> std::task::Poll<F::Output> {
+  std::future::poll_fn(|cx| std::task::Poll::Ready(fut.as_mut().poll(cx))).await
+}
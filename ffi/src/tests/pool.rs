@@ -0,0 +1,47 @@
+use crate::futures::{FFIFuture, implements::create_future};
+
+#[test]
+fn test_states_freed_on_other_threads() {
+  // Allocated here, freed by the thread awaiting them
+  let futures: Vec<_> = (0..1_000u32)
+    .map(|i| {
+      FFIFuture::new(create_future(async move {
+        tokio::task::yield_now().await;
+
+        i
+      }))
+    })
+    .collect();
+
+  let out = std::thread::spawn(move || {
+    let rt = tokio::runtime::Builder::new_current_thread()
+      .build()
+      .unwrap();
+
+    rt.block_on(async move {
+      let mut out = vec![];
+      for fut in futures {
+        out.push(fut.await.unwrap());
+      }
+
+      out
+    })
+  })
+  .join()
+  .unwrap();
+
+  assert_eq!(out, (0..1_000).collect::<Vec<_>>());
+}
+
+#[test]
+#[cfg(feature = "pool")]
+fn test_state_reused() {
+  let task = create_future(async { 1u8 });
+  let first = task.as_raw();
+
+  drop(task);
+
+  // The freed state is handed out again to the next task of the same size class
+  let task = create_future(async { 2u8 });
+  assert_eq!(task.as_raw(), first);
+}
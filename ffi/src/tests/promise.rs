@@ -0,0 +1,79 @@
+use std::{
+  ffi::c_void,
+  sync::atomic::{AtomicU64, Ordering},
+  thread,
+  time::Duration,
+};
+
+use crate::futures::{
+  FFIFuture, FutureError, Result,
+  promise::{FfiResolver, promise},
+};
+
+#[tokio::test]
+async fn test_resolve_from_thread() {
+  let (task, resolver) = promise::<u64, u32>();
+
+  thread::spawn(move || {
+    thread::sleep(Duration::from_millis(10));
+    assert!(resolver.resolve(5));
+  });
+
+  assert_eq!(FFIFuture::new(task).await, Ok(5));
+
+  let (task, resolver) = promise::<u64, u32>();
+  assert!(resolver.reject(3));
+  assert_eq!(FFIFuture::new(task).await, Err(FutureError::Failed(3)));
+}
+
+/// Stands in for a C library, which only sees the fields of the resolver
+extern "C" fn c_library(resolver: FfiResolver<u64>) {
+  (resolver._resolve)(resolver._state, 9);
+
+  // Consumed by `_resolve`
+  std::mem::forget(resolver);
+}
+
+#[tokio::test]
+async fn test_resolver_across_ffi() {
+  let (task, resolver) = promise::<u64, _>();
+  c_library(resolver);
+  assert_eq!(FFIFuture::new(task).await, Ok(9));
+
+  let (task, resolver) = promise::<u64, u32>();
+  drop(resolver);
+
+  let err = FFIFuture::new(task).await.unwrap_err();
+  assert!(err.panic_message().unwrap().contains("without resolving"));
+
+  let (task, resolver) = promise::<u64, u32>();
+  drop(task);
+  assert!(!resolver.resolve(1));
+}
+
+extern "C" fn store(ctx: *mut c_void, output: Result<u64, u32>) {
+  let value = match output.into_data() {
+    crate::futures::MaybeData::Some(x) => x,
+    _ => u64::MAX,
+  };
+
+  unsafe { (*(ctx as *const AtomicU64)).store(value, Ordering::Release) };
+}
+
+#[test]
+fn test_promise_on_complete() {
+  for resolve_first in [true, false] {
+    let slot = AtomicU64::new(0);
+    let (task, resolver) = promise::<u64, u32>();
+
+    if resolve_first {
+      resolver.resolve(7);
+      task.on_complete(store, &slot as *const _ as _);
+    } else {
+      task.on_complete(store, &slot as *const _ as _);
+      thread::spawn(move || resolver.resolve(7)).join().unwrap();
+    }
+
+    assert_eq!(slot.load(Ordering::Acquire), 7);
+  }
+}
@@ -0,0 +1,84 @@
+use std::alloc::Layout;
+
+use crate::{
+  boxed::RTBox,
+  vector::{Vector, raw::RawVector},
+};
+
+#[test]
+fn test_push_pop() {
+  let mut vec = unsafe { RawVector::new(Layout::from_size_align(12, 4).unwrap(), None) };
+
+  for i in 0..10u8 {
+    unsafe { vec.push(&[i; 12]) };
+  }
+
+  assert_eq!(vec.len(), 10);
+  assert!(vec.cap() >= 10);
+  assert_eq!(vec.get(3), Some(&[3u8; 12][..]));
+  assert_eq!(vec.get(10), None);
+
+  unsafe {
+    assert!(vec.set(3, &[42; 12]));
+    assert!(!vec.set(10, &[42; 12]));
+  }
+  assert_eq!(vec.get(3), Some(&[42u8; 12][..]));
+
+  let mut out = [0u8; 12];
+  assert!(vec.pop(&mut out));
+  assert_eq!(out, [9; 12]);
+  assert_eq!(vec.len(), 9);
+
+  let ptr = vec.get_ptr(0).unwrap();
+  assert!(ptr.as_ptr().addr() % 4 == 0);
+}
+
+#[test]
+#[should_panic]
+fn test_size_mismatch() {
+  let mut vec = RawVector::of::<u32>();
+
+  unsafe { vec.push(&[0; 8]) };
+}
+
+#[test]
+fn test_vector_roundtrip() {
+  let mut vec = Vector::<u64>::new();
+  vec.extend(0..100);
+
+  let mut raw = RawVector::from(vec);
+  assert_eq!(raw.len(), 100);
+  assert_eq!(raw.get(7), Some(&7u64.to_ne_bytes()[..]));
+
+  unsafe { raw.push(&1000u64.to_ne_bytes()) };
+
+  let raw = match unsafe { raw.try_into_vector::<u32>() } {
+    Ok(_) => panic!("Layout of u32 must not match u64"),
+    Err(raw) => raw,
+  };
+
+  let vec = unsafe { raw.try_into_vector::<u64>() }.ok().unwrap();
+  assert_eq!(vec.len(), 101);
+  assert_eq!(vec[100], 1000);
+  assert_eq!(vec[42], 42);
+}
+
+#[test]
+fn test_drop_fn() {
+  let mut vec = Vector::<RTBox<u64>>::new();
+  vec.push(RTBox::new(1).unwrap());
+  vec.push(RTBox::new(2).unwrap());
+
+  let mut raw = RawVector::from(vec);
+  assert!(raw.drop_fn().is_some());
+
+  let mut out = [0u8; size_of::<RTBox<u64>>()];
+  assert!(raw.pop(&mut out));
+
+  // Ownership of the box moved into `out`
+  let boxed = unsafe { std::mem::transmute::<[u8; size_of::<RTBox<u64>>()], RTBox<u64>>(out) };
+  assert_eq!(*boxed, 2);
+
+  // `*mut u64` has no drop function, so it must not be accepted
+  assert!(unsafe { raw.try_into_vector::<*mut u64>() }.is_err());
+}
@@ -0,0 +1,33 @@
+use crate::{string::str::SharableStr, vector::Vector};
+
+#[test]
+fn test_vector_into_str() {
+  let mut bytes = Vector::<u8>::new();
+  bytes.extend_from_slice("Hello, Sa!".as_bytes());
+
+  let data = bytes.as_ptr();
+  let string = SharableStr::from_utf8(bytes).unwrap();
+
+  assert_eq!(&*string, "Hello, Sa!");
+  assert_eq!(string.as_ptr(), data);
+
+  let mut bytes = string.into_bytes();
+  assert_eq!(bytes.as_ptr(), data);
+
+  bytes.extend_from_slice(" Bye!".as_bytes());
+  assert_eq!(&*bytes, b"Hello, Sa! Bye!");
+}
+
+#[test]
+fn test_str_into_vector() {
+  let string = SharableStr::create("ünïcödé");
+
+  let mut bytes = Vector::from(string);
+  assert_eq!(&*bytes, "ünïcödé".as_bytes());
+
+  bytes.push(0xFF);
+
+  let err = SharableStr::try_from(bytes).err().unwrap();
+  assert_eq!(err.utf8_error().valid_up_to(), "ünïcödé".len());
+  assert_eq!(err.into_bytes().len(), "ünïcödé".len() + 1);
+}
@@ -0,0 +1,54 @@
+use crate::{
+  boxed::RTBox,
+  vector::{
+    Vector,
+    small::{FfiSmallVec, SmallVecTag},
+  },
+};
+
+#[test]
+fn test_inline_then_spill() {
+  let mut vec = FfiSmallVec::<u32, 4>::new();
+
+  vec.extend(0..4);
+  assert_eq!(vec.tag(), SmallVecTag::Inline);
+  assert_eq!(vec.cap(), 4);
+  assert_eq!(&vec[..], &[0, 1, 2, 3]);
+
+  vec.push(4);
+  assert_eq!(vec.tag(), SmallVecTag::Heap);
+  assert!(vec.cap() >= 5);
+  assert_eq!(&vec[..], &[0, 1, 2, 3, 4]);
+
+  assert_eq!(vec.pop(), Some(4));
+  assert_eq!(vec.len(), 4);
+}
+
+#[test]
+fn test_vector_conversion() {
+  let mut vec = FfiSmallVec::<RTBox<u64>, 2>::new();
+  vec.push(RTBox::new(1).unwrap());
+
+  let mut vector = vec.into_vector();
+  vector.push(RTBox::new(2).unwrap());
+  assert_eq!(vector.len(), 2);
+
+  let mut vec = FfiSmallVec::<RTBox<u64>, 2>::from(vector);
+  assert!(vec.spilled());
+  assert_eq!(*vec.pop().unwrap(), 2);
+  assert_eq!(*vec[0], 1);
+
+  let empty: Vector<u8> = FfiSmallVec::<u8, 8>::new().into_vector();
+  assert_eq!(empty.len(), 0);
+}
+
+#[test]
+fn test_drop_inline() {
+  let mut vec = FfiSmallVec::<RTBox<u64>, 4>::new();
+
+  for i in 0..3 {
+    vec.push(RTBox::new(i).unwrap());
+  }
+
+  assert_eq!(vec.iter().map(|x| **x).sum::<u64>(), 3);
+}
@@ -0,0 +1,104 @@
+use std::{
+  future::Future,
+  pin::Pin,
+  sync::atomic::{AtomicUsize, Ordering},
+  task::{Context, Poll, Waker},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::RTBox,
+};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(C)]
+struct Large([u64; 512]);
+
+unsafe impl FFISafe for Large {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl Drop for Large {
+  fn drop(&mut self) {
+    DROPS.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+#[test]
+fn test_zeroed() {
+  let boxed = RTBox::<Large>::new_zeroed().unwrap();
+  let boxed = unsafe { boxed.assume_init() };
+
+  assert!(boxed.0.iter().all(|x| *x == 0));
+}
+
+#[test]
+fn test_uninit_in_place() {
+  let before = DROPS.load(Ordering::Relaxed);
+
+  let boxed = RTBox::<Large>::new_uninit().unwrap();
+
+  unsafe {
+    let data = boxed.as_mut_ptr() as *mut u64;
+
+    for i in 0..512 {
+      data.add(i).write(i as u64);
+    }
+  }
+
+  let boxed = unsafe { boxed.assume_init() };
+  assert_eq!(boxed.0[511], 511);
+
+  // The header now drops `Large`
+  drop(boxed);
+  assert!(DROPS.load(Ordering::Relaxed) > before);
+
+  let boxed = RTBox::<u32>::new_uninit().unwrap().write(7);
+  assert_eq!(*boxed, 7);
+}
+
+struct Count(u32);
+
+impl Future for Count {
+  type Output = u32;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    if self.0 == 3 {
+      return Poll::Ready(self.0);
+    }
+
+    self.0 += 1;
+    cx.waker().wake_by_ref();
+
+    Poll::Pending
+  }
+}
+
+unsafe impl FFISafe for Count {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+#[test]
+fn test_pin() {
+  let mut fut = RTBox::pin(Count(0)).unwrap();
+  let data = &*fut as *const Count;
+
+  let mut cx = Context::from_waker(Waker::noop());
+
+  loop {
+    if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+      assert_eq!(out, 3);
+      break;
+    }
+  }
+
+  // Moving the pinned box does not move the future
+  let moved = fut;
+  assert_eq!(&*moved as *const Count, data);
+}
@@ -0,0 +1,95 @@
+use std::{
+  any::Any,
+  ffi::c_void,
+  fmt::Display,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  boxed::RTBox, string::str::SharableStr, vector::Vector,
+};
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(C, align(32))]
+struct Counted(u64);
+
+unsafe impl FFISafe for Counted {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl Display for Counted {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Counted({})", self.0)
+  }
+}
+
+impl Drop for Counted {
+  fn drop(&mut self) {
+    DROPS.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+#[test]
+fn test_thin() {
+  assert_eq!(size_of::<RTBox<[u64]>>(), size_of::<*const c_void>());
+  assert_eq!(size_of::<RTBox<str>>(), size_of::<*const c_void>());
+  assert_eq!(size_of::<RTBox<dyn Any>>(), size_of::<*const c_void>());
+}
+
+#[test]
+fn test_slice_and_str() {
+  let mut slice = RTBox::<[u64]>::new_slice(&[1, 2, 3]).unwrap();
+  slice[1] = 20;
+  assert_eq!(&*slice, &[1, 20, 3]);
+
+  let string = RTBox::<str>::new_str("Hello, Sa!").unwrap();
+  assert_eq!(&*string, "Hello, Sa!");
+
+  let string = RTBox::<str>::from(SharableStr::create("shared"));
+  assert_eq!(&*string, "shared");
+
+  // Travels as a thin pointer
+  let raw = string.into_thin_raw();
+  let string = unsafe { RTBox::<str>::from_thin_raw(raw) }.unwrap();
+  assert_eq!(string.len(), 6);
+}
+
+#[test]
+fn test_from_vector() {
+  let before = DROPS.load(Ordering::Relaxed);
+
+  let mut vec = Vector::new();
+  for i in 0..10 {
+    vec.push(Counted(i));
+  }
+
+  let data = vec.as_ptr();
+  let boxed = RTBox::<[Counted]>::from(vec);
+
+  assert_eq!(boxed.as_ptr() as *const Counted, data);
+  assert_eq!(boxed.len(), 10);
+  assert_eq!(boxed[7].0, 7);
+
+  drop(boxed);
+  assert!(DROPS.load(Ordering::Relaxed) - before >= 10);
+}
+
+#[test]
+fn test_dyn() {
+  let before = DROPS.load(Ordering::Relaxed);
+
+  let boxed = RTBox::<dyn Display>::new_unsize(Counted(42)).unwrap();
+  assert_eq!(boxed.to_string(), "Counted(42)");
+  assert_eq!(boxed.as_ptr().addr() % 32, 0);
+
+  let any = RTBox::<dyn Any>::new_unsize(5u32).unwrap();
+  assert_eq!(any.downcast_ref::<u32>(), Some(&5));
+
+  drop(boxed);
+  assert!(DROPS.load(Ordering::Relaxed) > before);
+}
@@ -0,0 +1,76 @@
+use crate::{
+  boxed::RTBox,
+  vector::{Vector, deque::FfiVecDeque},
+};
+
+#[test]
+fn test_push_pop_both_ends() {
+  let mut deque = FfiVecDeque::<u32>::new();
+
+  for i in 0..10 {
+    deque.push_back(i);
+    deque.push_front(100 + i);
+  }
+
+  assert_eq!(deque.len(), 20);
+  assert_eq!(deque.get(0), Some(&109));
+  assert_eq!(deque.get(19), Some(&9));
+
+  assert_eq!(deque.pop_front(), Some(109));
+  assert_eq!(deque.pop_back(), Some(9));
+
+  let (front, back) = deque.as_slices();
+  let all = front.iter().chain(back).copied().collect::<Vec<_>>();
+
+  let mut expected = (100..109).rev().collect::<Vec<_>>();
+  expected.extend(0..9);
+  assert_eq!(all, expected);
+
+  assert_eq!(deque.make_contiguous(), &expected[..]);
+}
+
+#[test]
+fn test_grow_while_wrapped() {
+  let mut deque = FfiVecDeque::<u64>::new();
+
+  // Shift the head around the ring before growing
+  for round in 0..5 {
+    for i in 0..7 {
+      deque.push_back(round * 10 + i);
+    }
+
+    for i in 0..7 {
+      assert_eq!(deque.pop_front(), Some(round * 10 + i));
+    }
+  }
+
+  for i in 0..3 {
+    deque.push_back(i);
+  }
+  for i in 3..40 {
+    deque.push_back(i);
+  }
+
+  assert_eq!(deque.len(), 40);
+  for i in 0..40 {
+    assert_eq!(deque.get(i as usize), Some(&i));
+  }
+}
+
+#[test]
+fn test_vector_conversion() {
+  let mut vec = Vector::<RTBox<u64>>::new();
+  for i in 0..5 {
+    vec.push(RTBox::new(i).unwrap());
+  }
+
+  let mut deque = FfiVecDeque::from(vec);
+  assert_eq!(deque.len(), 5);
+
+  deque.push_front(RTBox::new(100).unwrap());
+  assert_eq!(*deque.pop_back().unwrap(), 4);
+
+  let vec = Vector::from(deque);
+  let values = vec.iter().map(|x| **x).collect::<Vec<_>>();
+  assert_eq!(values, [100, 0, 1, 2, 3]);
+}
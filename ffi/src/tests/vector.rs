@@ -0,0 +1,25 @@
+use std::{sync::Arc, thread};
+
+use crate::vector::Vector;
+
+#[test]
+fn test_send_sync() {
+  let mut vector = Vector::<u64>::new();
+  vector.extend([1, 2, 3]);
+
+  // Moved to another thread, which frees it
+  let sum = thread::spawn(move || vector.iter().sum::<u64>())
+    .join()
+    .unwrap();
+  assert_eq!(sum, 6);
+
+  let mut shared = Vector::<u64>::new();
+  shared.extend([4, 5]);
+  let shared = Arc::new(shared);
+
+  let reader = {
+    let shared = shared.clone();
+    thread::spawn(move || shared.len())
+  };
+  assert_eq!(reader.join().unwrap(), shared.len());
+}
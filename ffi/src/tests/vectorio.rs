@@ -0,0 +1,64 @@
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+
+use crate::vector::{Vector, io::VectorCursor};
+
+#[test]
+fn test_write() {
+  let mut vec = Vector::<u8>::new();
+
+  write!(vec, "Hello {}!", 42).unwrap();
+  vec.write_all(b" Bye").unwrap();
+
+  assert_eq!(&*vec, b"Hello 42! Bye");
+}
+
+#[test]
+fn test_cursor() {
+  let mut vec = Vector::<u8>::new();
+  vec.extend_from_slice(b"line one\nline two\n");
+
+  let mut cursor = VectorCursor::new(vec);
+
+  let mut line = String::new();
+  cursor.read_line(&mut line).unwrap();
+  assert_eq!(line, "line one\n");
+  assert_eq!(cursor.position(), 9);
+
+  let mut buf = [0u8; 4];
+  cursor.read_exact(&mut buf).unwrap();
+  assert_eq!(&buf, b"line");
+
+  assert_eq!(cursor.seek(SeekFrom::End(-4)).unwrap(), 14);
+  cursor.write_all(b"2 and three").unwrap();
+
+  assert!(cursor.seek(SeekFrom::Current(-100)).is_err());
+
+  cursor.rewind().unwrap();
+  let mut out = String::new();
+  cursor.read_to_string(&mut out).unwrap();
+  assert_eq!(out, "line one\nline 2 and three");
+
+  cursor.seek(SeekFrom::Start(30)).unwrap();
+  cursor.write_all(b"!").unwrap();
+  assert_eq!(cursor.get_ref().len(), 31);
+  assert_eq!(cursor.get_ref()[29], 0);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes() {
+  use bytes::{Buf, BufMut};
+
+  let mut vec = Vector::<u8>::new();
+  vec.put_u32(0xDEADBEEF);
+  vec.put_slice(&[1; 100]);
+  vec.put_bytes(7, 3);
+
+  assert_eq!(vec.len(), 107);
+
+  let mut cursor = VectorCursor::new(vec);
+  assert_eq!(cursor.get_u32(), 0xDEADBEEF);
+  cursor.advance(100);
+  assert_eq!(cursor.remaining(), 3);
+  assert_eq!(cursor.chunk(), &[7, 7, 7]);
+}
@@ -0,0 +1,299 @@
+use core::ffi::c_void;
+use core::{cell::UnsafeCell, marker::PhantomData, mem::MaybeUninit, ptr};
+use std::hint::cold_path;
+use std::sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+};
+
+/// The first segment holds `1 << FIRST_SHIFT` elements, every next one is twice as large
+const FIRST_SHIFT: u32 = 4;
+const SEGMENTS: usize = (usize::BITS - FIRST_SHIFT) as usize;
+
+/// Nobody wrote this slot yet
+const EMPTY: u8 = 0;
+/// The value of this slot has been written
+const WRITTEN: u8 = 1;
+/// The publisher stopped at this slot, its writer has to take over publishing
+const WAITED: u8 = 2;
+
+#[repr(C)]
+struct Slot<T> {
+  state: AtomicU8,
+  value: UnsafeCell<MaybeUninit<T>>,
+}
+
+#[inline(always)]
+const fn seg_len(seg: usize) -> usize {
+  1 << (seg as u32 + FIRST_SHIFT)
+}
+
+#[inline(always)]
+/// Maps an index to (segment, offset within the segment)
+const fn locate(index: usize) -> (usize, usize) {
+  let n = index + (1 << FIRST_SHIFT);
+  let bit = usize::BITS - 1 - n.leading_zeros();
+
+  let seg = (bit - FIRST_SHIFT) as usize;
+
+  (seg, n - (1 << bit))
+}
+
+#[repr(C)]
+/// An append-only vector which can be pushed into from many threads without locking
+///
+/// The elements are stored in segments allocated through salloc which are never moved,
+/// so references into the vector stay valid as long as the vector is borrowed.
+///
+/// Readers only ever see the prefix of elements that have been completely written,
+/// see [`FfiConcurrentVec::snapshot`].
+pub struct FfiConcurrentVec<T: FFISafe> {
+  /// Number of slots handed out to writers
+  reserved: AtomicUsize,
+  /// Number of contiguous, completely written elements
+  len: AtomicUsize,
+
+  segments: [AtomicPtr<c_void>; SEGMENTS],
+
+  _t: PhantomData<T>,
+}
+
+unsafe impl<T: FFISafe> FFISafe for FfiConcurrentVec<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+unsafe impl<T: FFISafe + Send> Send for FfiConcurrentVec<T> {}
+unsafe impl<T: FFISafe + Send + Sync> Sync for FfiConcurrentVec<T> {}
+
+impl<T: FFISafe> Default for FfiConcurrentVec<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: FFISafe> FfiConcurrentVec<T> {
+  /// Creates an empty vector. This does not allocate.
+  pub const fn new() -> Self {
+    Self {
+      reserved: AtomicUsize::new(0),
+      len: AtomicUsize::new(0),
+      segments: [const { AtomicPtr::new(ptr::null_mut()) }; SEGMENTS],
+      _t: PhantomData,
+    }
+  }
+
+  #[inline(always)]
+  /// Number of elements that can be read, same as `self.snapshot().len()`
+  pub fn len(&self) -> usize {
+    self.len.load(Ordering::Acquire)
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Allocates the segment if no other writer did
+  #[cold]
+  fn alloc_segment(&self, seg: usize) -> *mut Slot<T> {
+    // Zeroed, so every slot starts as EMPTY
+    let new = unsafe {
+      salloc::aligned_zalloc(
+        size_of::<Slot<T>>() * seg_len(seg),
+        align_of::<Slot<T>>().max(size_of::<*const c_void>()),
+      )
+    };
+
+    if new.is_null() {
+      panic!("Allocation Failed");
+    }
+
+    match self.segments[seg].compare_exchange(
+      ptr::null_mut(),
+      new,
+      Ordering::AcqRel,
+      Ordering::Acquire,
+    ) {
+      Ok(_) => new as _,
+      Err(other) => {
+        // Someone beat us to it
+        unsafe { salloc::aligned_free(new) };
+
+        other as _
+      }
+    }
+  }
+
+  #[inline(always)]
+  fn slot(&self, index: usize) -> *mut Slot<T> {
+    let (seg, offset) = locate(index);
+
+    let mut base = self.segments[seg].load(Ordering::Acquire) as *mut Slot<T>;
+
+    if base.is_null() {
+      cold_path();
+      base = self.alloc_segment(seg);
+    }
+
+    unsafe { base.add(offset) }
+  }
+
+  /// Appends an element, returning its index
+  ///
+  /// This is lock-free and may be called from any number of threads at once.
+  pub fn push(&self, value: T) -> usize {
+    let index = self.reserved.fetch_add(1, Ordering::Relaxed);
+
+    if index >= isize::MAX as usize {
+      cold_path();
+      panic!("capacity overflow");
+    }
+
+    let prev = unsafe {
+      let slot = &*self.slot(index);
+
+      (*slot.value.get()).write(value);
+      slot.state.swap(WRITTEN, Ordering::AcqRel)
+    };
+
+    // The first slot has nobody before it to wait on it
+    if prev == WAITED || index == 0 {
+      self.publish(index);
+    }
+
+    index
+  }
+
+  /// Moves `len` forward from `start` over every written slot
+  ///
+  /// There is only ever one publisher. It stops at the first slot that is not
+  /// written yet, marking it as `WAITED` so that its writer continues from there.
+  fn publish(&self, start: usize) {
+    let mut len = start;
+
+    loop {
+      let slot = unsafe { &*self.slot(len) };
+
+      match slot
+        .state
+        .compare_exchange(EMPTY, WAITED, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => return,
+        Err(_) => {
+          len += 1;
+          self.len.store(len, Ordering::Release);
+        }
+      }
+    }
+  }
+
+  /// Returns the element at `index` if it has been published
+  pub fn get(&self, index: usize) -> Option<&T> {
+    if index >= self.len() {
+      return None;
+    }
+
+    Some(unsafe { self.get_unchecked(index) })
+  }
+
+  #[inline(always)]
+  /// # Safety
+  ///
+  /// `index` must be less than a length previously returned by this vector
+  unsafe fn get_unchecked(&self, index: usize) -> &T {
+    let (seg, offset) = locate(index);
+
+    unsafe {
+      let base = self.segments[seg].load(Ordering::Acquire) as *mut Slot<T>;
+
+      (*(*base.add(offset)).value.get()).assume_init_ref()
+    }
+  }
+
+  /// Returns a consistent view of the elements published so far
+  ///
+  /// Elements pushed after the snapshot was taken are not part of it.
+  pub fn snapshot(&self) -> Snapshot<'_, T> {
+    Snapshot {
+      vec: self,
+      len: self.len(),
+    }
+  }
+
+  /// Drops every element, keeping the segments for reuse
+  pub fn clear(&mut self) {
+    let reserved = *self.reserved.get_mut();
+
+    // The slot after the last one may be marked as WAITED
+    for i in 0..=reserved {
+      let (seg, offset) = locate(i);
+      let base = *self.segments[seg].get_mut() as *mut Slot<T>;
+
+      if base.is_null() {
+        continue;
+      }
+
+      unsafe {
+        let slot = &mut *base.add(offset);
+
+        if *slot.state.get_mut() == WRITTEN {
+          slot.value.get_mut().assume_init_drop();
+        }
+
+        *slot.state.get_mut() = EMPTY;
+      }
+    }
+
+    *self.reserved.get_mut() = 0;
+    *self.len.get_mut() = 0;
+  }
+}
+
+/// A view of the first `len` elements of a [`FfiConcurrentVec`]
+pub struct Snapshot<'a, T: FFISafe> {
+  vec: &'a FfiConcurrentVec<T>,
+  len: usize,
+}
+
+impl<'a, T: FFISafe> Snapshot<'a, T> {
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn get(&self, index: usize) -> Option<&'a T> {
+    if index >= self.len {
+      return None;
+    }
+
+    Some(unsafe { self.vec.get_unchecked(index) })
+  }
+
+  pub fn iter(&self) -> impl ExactSizeIterator<Item = &'a T> + use<'a, T> {
+    let vec = self.vec;
+
+    (0..self.len).map(move |i| unsafe { vec.get_unchecked(i) })
+  }
+}
+
+impl<T: FFISafe> Drop for FfiConcurrentVec<T> {
+  fn drop(&mut self) {
+    self.clear();
+
+    for seg in self.segments.iter_mut() {
+      let base = *seg.get_mut();
+
+      if !base.is_null() {
+        unsafe { salloc::aligned_free(base) };
+      }
+    }
+  }
+}
@@ -0,0 +1,505 @@
+use core::ffi::c_void;
+use core::{num::NonZeroUsize, ptr};
+use std::hint::cold_path;
+use std::mem::{MaybeUninit, forget, needs_drop, offset_of};
+use std::ptr::NonNull;
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  hardened::{self, TAG_DEQUE, TAG_VECTOR},
+  vector::{self, Vector, VectorHeaderVTable},
+};
+
+#[repr(C)]
+/// Laid out like `VectorHeaderVTable<T>` with `head` in front of `len`, so the data
+/// offset is never smaller than the one of a [`Vector`]
+pub struct VecDequeHeaderVTable<T: FFISafe + Sized> {
+  #[cfg(feature = "hardened")]
+  _guard: hardened::Guard,
+
+  /// Index of the first element in the ring
+  head: usize,
+  len: usize,
+  cap: usize,
+
+  data: MaybeUninit<T>,
+}
+
+/// Returns the offset of T within VecDequeHeaderVTable<T>.
+pub const fn data_offset<T: FFISafe>() -> isize {
+  offset_of!(VecDequeHeaderVTable<T>, data) as _
+}
+
+/// Negative offset from T back to the Header:
+pub const fn header_offset<T: FFISafe>() -> isize {
+  -data_offset::<T>()
+}
+
+const fn calc<T: FFISafe + Sized>(count: NonZeroUsize) -> usize {
+  ((count.get() - 1) * size_of::<T>()) + size_of::<VecDequeHeaderVTable<T>>()
+}
+
+const fn align<T: FFISafe + Sized>() -> usize {
+  let align = align_of::<VecDequeHeaderVTable<T>>();
+  let ptr = size_of::<*const c_void>();
+
+  if align > ptr { align } else { ptr }
+}
+
+#[repr(C)]
+/// A ring buffer allocated through salloc
+///
+/// Just like [`Vector`], this is a pointer to the first slot of the buffer with the
+/// `head`, `len` & `cap` header in front of it. The elements live in the slots
+/// `head..head + len`, wrapping around at `cap`.
+pub struct FfiVecDeque<T: FFISafe + Sized> {
+  ptr: NonNull<T>,
+}
+
+unsafe impl<T: FFISafe + Sized> FFISafe for FfiVecDeque<T> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl<T: FFISafe + Sized> Default for FfiVecDeque<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: FFISafe + Sized> FfiVecDeque<T> {
+  pub fn new() -> Self {
+    const DEF_CAP: usize = 2;
+
+    let ptr = unsafe {
+      salloc::aligned_malloc(
+        calc::<T>(NonZeroUsize::new_unchecked(DEF_CAP)),
+        align::<T>(),
+      )
+    };
+
+    if ptr.is_null() {
+      panic!("Allocation Failed");
+    }
+
+    unsafe {
+      // SAFETY:
+      //
+      // The data is not accessed, and hence is safe
+      ptr::write(
+        ptr as *mut VecDequeHeaderVTable<T>,
+        VecDequeHeaderVTable {
+          #[cfg(feature = "hardened")]
+          _guard: hardened::Guard::new(TAG_DEQUE),
+          head: 0,
+          len: 0,
+          cap: DEF_CAP,
+          data: MaybeUninit::uninit(),
+        },
+      );
+    }
+
+    Self {
+      ptr: unsafe { NonNull::new_unchecked(ptr.byte_offset(data_offset::<T>()) as *mut T) },
+    }
+  }
+
+  #[inline(always)]
+  fn header(&self) -> *mut VecDequeHeaderVTable<T> {
+    unsafe { self.ptr.as_ptr().byte_offset(header_offset::<T>()) as _ }
+  }
+
+  #[inline(always)]
+  #[track_caller]
+  /// Checks the header canary, see the `hardened` feature
+  fn verify(&self) {
+    unsafe { hardened::verify(self.header() as _, TAG_DEQUE) };
+  }
+
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    unsafe { (*self.header()).len }
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  #[inline(always)]
+  pub fn cap(&self) -> usize {
+    unsafe { (*self.header()).cap }
+  }
+
+  #[inline(always)]
+  fn head(&self) -> usize {
+    unsafe { (*self.header()).head }
+  }
+
+  #[inline(always)]
+  fn set_head_len(&mut self, head: usize, len: usize) {
+    unsafe {
+      let header = self.header();
+
+      (*header).head = head;
+      (*header).len = len;
+    }
+  }
+
+  #[inline(always)]
+  /// Physical slot of the logical index, `index` must be `<= cap`
+  fn wrap(&self, index: usize) -> usize {
+    let idx = self.head() + index;
+    let cap = self.cap();
+
+    if idx >= cap { idx - cap } else { idx }
+  }
+
+  #[inline(always)]
+  fn slot(&self, physical: usize) -> *mut T {
+    unsafe { self.ptr.as_ptr().add(physical) }
+  }
+
+  /// Makes sure that the deque can hold at least `capacity` elements
+  pub fn allocate(&mut self, capacity: NonZeroUsize) {
+    let capacity = capacity.get();
+    let old_cap = self.cap();
+
+    if old_cap >= capacity {
+      return;
+    }
+
+    let new_cap = (old_cap * 2).max(capacity);
+
+    let new_block = unsafe {
+      salloc::aligned_realloc(
+        self.header() as _,
+        calc::<T>(NonZeroUsize::new_unchecked(new_cap)),
+        align::<T>(),
+      )
+    };
+
+    if new_block.is_null() {
+      panic!("Allocation Failed");
+    }
+
+    unsafe {
+      self.ptr = NonNull::new_unchecked(new_block.byte_offset(data_offset::<T>()) as _);
+      (*self.header()).cap = new_cap;
+    }
+
+    let head = self.head();
+    let len = self.len();
+
+    // The ring did not wrap around, nothing to fix
+    if head <= old_cap - len {
+      return;
+    }
+
+    let head_len = old_cap - head;
+    let tail_len = len - head_len;
+
+    unsafe {
+      if tail_len < head_len && tail_len <= new_cap - old_cap {
+        // Move the wrapped part right after the old end
+        ptr::copy_nonoverlapping(self.slot(0), self.slot(old_cap), tail_len);
+      } else {
+        // Move the head part to the end of the new buffer
+        let new_head = new_cap - head_len;
+        ptr::copy(self.slot(head), self.slot(new_head), head_len);
+
+        self.set_head_len(new_head, len);
+      }
+    }
+  }
+
+  #[inline(always)]
+  fn grow_one(&mut self) {
+    let len = self.len();
+
+    if len == self.cap() {
+      cold_path();
+      self.allocate(unsafe { NonZeroUsize::new_unchecked(len + 1) });
+    }
+  }
+
+  pub fn push_back(&mut self, value: T) {
+    self.grow_one();
+
+    let len = self.len();
+    let slot = self.wrap(len);
+
+    unsafe { ptr::write(self.slot(slot), value) };
+
+    self.set_head_len(self.head(), len + 1);
+  }
+
+  pub fn push_front(&mut self, value: T) {
+    self.grow_one();
+
+    let head = self.head();
+    let new_head = if head == 0 { self.cap() - 1 } else { head - 1 };
+
+    unsafe { ptr::write(self.slot(new_head), value) };
+
+    self.set_head_len(new_head, self.len() + 1);
+  }
+
+  pub fn pop_back(&mut self) -> Option<T> {
+    let len = self.len();
+
+    if len == 0 {
+      cold_path();
+      return None;
+    }
+
+    let slot = self.wrap(len - 1);
+    self.set_head_len(self.head(), len - 1);
+
+    Some(unsafe { ptr::read(self.slot(slot)) })
+  }
+
+  pub fn pop_front(&mut self) -> Option<T> {
+    let len = self.len();
+
+    if len == 0 {
+      cold_path();
+      return None;
+    }
+
+    let head = self.head();
+    let new_head = self.wrap(1);
+    self.set_head_len(if len == 1 { 0 } else { new_head }, len - 1);
+
+    Some(unsafe { ptr::read(self.slot(head)) })
+  }
+
+  pub fn get(&self, index: usize) -> Option<&T> {
+    if index >= self.len() {
+      return None;
+    }
+
+    Some(unsafe { &*self.slot(self.wrap(index)) })
+  }
+
+  pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+    if index >= self.len() {
+      return None;
+    }
+
+    Some(unsafe { &mut *self.slot(self.wrap(index)) })
+  }
+
+  /// Returns the contents as two slices, the front part followed by the
+  /// wrapped around part
+  pub fn as_slices(&self) -> (&[T], &[T]) {
+    self.verify();
+
+    let head = self.head();
+    let len = self.len();
+    let head_len = (self.cap() - head).min(len);
+
+    unsafe {
+      (
+        core::slice::from_raw_parts(self.slot(head), head_len),
+        core::slice::from_raw_parts(self.slot(0), len - head_len),
+      )
+    }
+  }
+
+  pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+    self.verify();
+
+    let head = self.head();
+    let len = self.len();
+    let head_len = (self.cap() - head).min(len);
+
+    unsafe {
+      (
+        core::slice::from_raw_parts_mut(self.slot(head), head_len),
+        core::slice::from_raw_parts_mut(self.slot(0), len - head_len),
+      )
+    }
+  }
+
+  /// Moves the elements so that they start at the first slot, returning them
+  /// as a single slice
+  pub fn make_contiguous(&mut self) -> &mut [T] {
+    let head = self.head();
+    let len = self.len();
+
+    if head != 0 {
+      unsafe {
+        let buf =
+          core::slice::from_raw_parts_mut(self.ptr.as_ptr() as *mut MaybeUninit<T>, self.cap());
+
+        buf.rotate_left(head);
+      }
+
+      self.set_head_len(0, len);
+    }
+
+    unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), len) }
+  }
+
+  /// Gives up ownership, returning the pointer to the first slot of the buffer
+  ///
+  /// The deque must be given back to [`FfiVecDeque::from_raw`], possibly in another
+  /// dylib, to be freed.
+  pub fn into_raw(self) -> *mut T {
+    let data = self.ptr.as_ptr();
+    forget(self);
+
+    data
+  }
+
+  /// Takes ownership of a pointer returned by [`FfiVecDeque::into_raw`], `None` if it
+  /// is null
+  ///
+  /// # Safety
+  ///
+  /// The pointer must come from `into_raw` of a `FfiVecDeque<T>` built with the same saffi
+  /// version, and nobody else may own it.
+  pub unsafe fn from_raw(ptr: *mut T) -> Option<Self> {
+    Some(unsafe { Self::from_nonnull(NonNull::new(ptr)?) })
+  }
+
+  /// Same as [`FfiVecDeque::from_raw`] for a pointer that is known not to be null
+  ///
+  /// # Safety
+  ///
+  /// See [`FfiVecDeque::from_raw`]
+  pub unsafe fn from_nonnull(ptr: NonNull<T>) -> Self {
+    unsafe {
+      hardened::verify(
+        ptr.as_ptr().byte_offset(header_offset::<T>()) as _,
+        TAG_DEQUE,
+      )
+    };
+
+    Self { ptr }
+  }
+
+  /// Gives up ownership without ever freeing the deque, returning its elements
+  ///
+  /// The elements are made contiguous first.
+  pub fn leak<'a>(mut self) -> &'a mut [T] {
+    let len = self.make_contiguous().len();
+
+    unsafe { core::slice::from_raw_parts_mut(self.into_raw(), len) }
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`FfiVecDeque::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the deque is dropped or reallocates.
+  pub const fn as_raw(&self) -> *const T {
+    self.ptr.as_ptr()
+  }
+
+  /// Lends the pointer of [`FfiVecDeque::into_raw`] to `f`, e.g. to hand it to foreign
+  /// code for the duration of a call
+  ///
+  /// `f` may read and write the elements through it, but must neither free it, nor
+  /// reallocate it, nor keep it after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut T) -> R) -> R {
+    f(self.ptr.as_ptr())
+  }
+}
+
+impl<T: FFISafe + Sized> From<Vector<T>> for FfiVecDeque<T> {
+  /// This reuses the allocation of the vector, only moving the elements
+  /// to make room for the larger header
+  fn from(value: Vector<T>) -> Self {
+    const { assert!(data_offset::<T>() >= vector::data_offset::<T>()) };
+
+    let len = value.len();
+    let cap = value.cap();
+
+    let old_block = unsafe { value.into_raw().byte_offset(vector::header_offset::<T>()) };
+
+    unsafe {
+      let new_block = salloc::aligned_realloc(
+        old_block as _,
+        calc::<T>(NonZeroUsize::new_unchecked(cap.max(1))),
+        align::<T>(),
+      );
+
+      if new_block.is_null() {
+        panic!("Allocation Failed");
+      }
+
+      ptr::copy(
+        new_block.byte_offset(vector::data_offset::<T>()) as *const T,
+        new_block.byte_offset(data_offset::<T>()) as *mut T,
+        len,
+      );
+
+      // The first element now overlaps `data`, so the fields are written one by one
+      let header = new_block as *mut VecDequeHeaderVTable<T>;
+      hardened::retag(new_block, TAG_DEQUE);
+      (*header).head = 0;
+      (*header).len = len;
+      (*header).cap = cap.max(1);
+
+      Self {
+        ptr: NonNull::new_unchecked(new_block.byte_offset(data_offset::<T>()) as *mut T),
+      }
+    }
+  }
+}
+
+impl<T: FFISafe + Sized> From<FfiVecDeque<T>> for Vector<T> {
+  /// This reuses the allocation of the deque, the elements are rotated
+  /// into order first
+  ///
+  /// The vector header is smaller, so the block is large enough as it is.
+  fn from(mut value: FfiVecDeque<T>) -> Self {
+    value.make_contiguous();
+
+    let len = value.len();
+    let cap = value.cap();
+
+    unsafe {
+      let block = value.into_raw().byte_offset(header_offset::<T>()) as *mut c_void;
+
+      ptr::copy(
+        block.byte_offset(data_offset::<T>()) as *const T,
+        block.byte_offset(vector::data_offset::<T>()) as *mut T,
+        len,
+      );
+
+      let header = block as *mut VectorHeaderVTable<T>;
+      hardened::retag(block, TAG_VECTOR);
+      (*header).len = len;
+      (*header).cap = cap;
+
+      Vector::from_nonnull(NonNull::new_unchecked(
+        block.byte_offset(vector::data_offset::<T>()) as *mut T,
+      ))
+    }
+  }
+}
+
+impl<T: FFISafe + Sized> Drop for FfiVecDeque<T> {
+  fn drop(&mut self) {
+    unsafe {
+      if needs_drop::<T>() {
+        let (front, back) = self.as_mut_slices();
+
+        ptr::drop_in_place(front);
+        ptr::drop_in_place(back);
+      } else {
+        self.verify();
+      }
+
+      hardened::poison(
+        self.header() as _,
+        data_offset::<T>() as usize + self.cap() * size_of::<T>(),
+      );
+      salloc::aligned_free(self.header() as _)
+    };
+  }
+}
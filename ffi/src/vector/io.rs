@@ -0,0 +1,227 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+
+use crate::vector::Vector;
+
+impl Write for Vector<u8> {
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.extend_from_slice(buf);
+
+    Ok(buf.len())
+  }
+
+  #[inline]
+  fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+    self.extend_from_slice(buf);
+
+    Ok(())
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// A cursor over a byte [`Vector`], which implements [`Read`], [`BufRead`] and [`Seek`]
+///
+/// Writing through the cursor overwrites the bytes at the current position,
+/// growing the vector when writing past its end.
+pub struct VectorCursor {
+  inner: Vector<u8>,
+  pos: u64,
+}
+
+impl VectorCursor {
+  pub const fn new(inner: Vector<u8>) -> Self {
+    Self { inner, pos: 0 }
+  }
+
+  pub fn into_inner(self) -> Vector<u8> {
+    self.inner
+  }
+
+  pub const fn get_ref(&self) -> &Vector<u8> {
+    &self.inner
+  }
+
+  pub const fn get_mut(&mut self) -> &mut Vector<u8> {
+    &mut self.inner
+  }
+
+  pub const fn position(&self) -> u64 {
+    self.pos
+  }
+
+  pub const fn set_position(&mut self, pos: u64) {
+    self.pos = pos;
+  }
+
+  /// The bytes after the current position
+  pub fn remaining_slice(&self) -> &[u8] {
+    let start = (self.pos as usize).min(self.inner.len());
+
+    &(*self.inner)[start..]
+  }
+}
+
+impl Read for VectorCursor {
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let n = Read::read(&mut self.remaining_slice(), buf)?;
+    self.pos += n as u64;
+
+    Ok(n)
+  }
+
+  fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+    Read::read_exact(&mut self.remaining_slice(), buf)?;
+    self.pos += buf.len() as u64;
+
+    Ok(())
+  }
+}
+
+impl BufRead for VectorCursor {
+  fn fill_buf(&mut self) -> io::Result<&[u8]> {
+    Ok(self.remaining_slice())
+  }
+
+  fn consume(&mut self, amt: usize) {
+    self.pos += amt as u64;
+  }
+}
+
+impl Seek for VectorCursor {
+  fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+    let (base, offset) = match style {
+      SeekFrom::Start(n) => {
+        self.pos = n;
+        return Ok(n);
+      }
+      SeekFrom::End(n) => (self.inner.len() as u64, n),
+      SeekFrom::Current(n) => (self.pos, n),
+    };
+
+    match base.checked_add_signed(offset) {
+      Some(n) => {
+        self.pos = n;
+        Ok(n)
+      }
+      None => Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "invalid seek to a negative or overflowing position",
+      )),
+    }
+  }
+
+  fn stream_position(&mut self) -> io::Result<u64> {
+    Ok(self.pos)
+  }
+}
+
+impl Write for VectorCursor {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let Ok(pos) = usize::try_from(self.pos) else {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "cursor position exceeds maximum possible vector length",
+      ));
+    };
+
+    let len = self.inner.len();
+
+    // Pad the gap with zeroes when the cursor is past the end
+    if pos > len {
+      self.inner.extend((len..pos).map(|_| 0));
+    }
+
+    let overlap = (self.inner.len() - pos).min(buf.len());
+    (*self.inner)[pos..pos + overlap].copy_from_slice(&buf[..overlap]);
+    self.inner.extend_from_slice(&buf[overlap..]);
+
+    self.pos += buf.len() as u64;
+
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+#[cfg(feature = "bytes")]
+mod bytes_impl {
+  use core::num::NonZeroUsize;
+
+  use bytes::{Buf, BufMut, buf::UninitSlice};
+
+  use super::*;
+
+  unsafe impl BufMut for Vector<u8> {
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+      isize::MAX as usize - self.len()
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+      let len = self.len();
+      let remaining = self.cap() - len;
+
+      if remaining < cnt {
+        panic!(
+          "advance out of bounds: the len is {} but advancing by {}",
+          remaining, cnt
+        );
+      }
+
+      self.set_len(len + cnt);
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+      const CHUNK: usize = 64;
+
+      let len = self.len();
+
+      if self.cap() == len {
+        self.allocate(None, unsafe { NonZeroUsize::new_unchecked(len + CHUNK) });
+      }
+
+      let cap = self.cap();
+
+      unsafe { UninitSlice::from_raw_parts_mut(self.as_mut_ptr().add(len), cap - len) }
+    }
+
+    #[inline]
+    fn put_slice(&mut self, src: &[u8]) {
+      self.extend_from_slice(src);
+    }
+  }
+
+  impl Buf for VectorCursor {
+    #[inline]
+    fn remaining(&self) -> usize {
+      self.remaining_slice().len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+      self.remaining_slice()
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+      let remaining = self.remaining();
+
+      if cnt > remaining {
+        panic!(
+          "advance out of bounds: the len is {} but advancing by {}",
+          remaining, cnt
+        );
+      }
+
+      self.pos += cnt as u64;
+    }
+  }
+}
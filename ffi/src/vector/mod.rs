@@ -13,10 +13,20 @@ use std::ptr::NonNull;
 use crate::{
   FFISafe,
   I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  hardened::{self, TAG_VECTOR},
 };
 
+pub mod concurrent;
+pub mod deque;
+pub mod io;
+pub mod raw;
+pub mod small;
+
 #[repr(C)]
 pub struct VectorHeaderVTable<T: FFISafe + Sized> {
+  #[cfg(feature = "hardened")]
+  _guard: hardened::Guard,
+
   len: usize,
   cap: usize,
 
@@ -47,6 +57,10 @@ unsafe impl<T: FFISafe + Sized> FFISafe for Vector<T> {
   }
 }
 
+// Owns its items like a `Vec`
+unsafe impl<T: FFISafe + Send> Send for Vector<T> {}
+unsafe impl<T: FFISafe + Sync> Sync for Vector<T> {}
+
 const fn calc<T: FFISafe + Sized>(count: NonZeroUsize) -> usize {
   ((count.get() - 1) * size_of::<T>()) + size_of::<VectorHeaderVTable<T>>()
 }
@@ -71,6 +85,8 @@ impl<T: FFISafe + Sized> Vector<T> {
       //
       // The data is not accessed, and hence is safe
       *(ptr as *mut VectorHeaderVTable<T>) = VectorHeaderVTable {
+        #[cfg(feature = "hardened")]
+        _guard: hardened::Guard::new(TAG_VECTOR),
         len: 0,
         cap: DEF_CAP,
         data: MaybeUninit::uninit(),
@@ -82,6 +98,35 @@ impl<T: FFISafe + Sized> Vector<T> {
     }
   }
 
+  #[inline(always)]
+  fn header(&self) -> *mut c_void {
+    unsafe { self.ptr.as_ptr().byte_offset(header_offset::<T>()) as _ }
+  }
+
+  #[inline(always)]
+  #[track_caller]
+  /// Checks the header canary, see the `hardened` feature
+  fn verify(&self) {
+    unsafe { hardened::verify(self.header(), TAG_VECTOR) };
+  }
+
+  #[inline(always)]
+  #[track_caller]
+  fn check_known(&self, known_len: Option<usize>, known_cap: Option<usize>) {
+    hardened::check_known("known_len", known_len, || self.len());
+    hardened::check_known("known_cap", known_cap, || self.cap());
+  }
+
+  #[inline(always)]
+  pub const fn as_ptr(&self) -> *const T {
+    self.ptr.as_ptr() as _
+  }
+
+  #[inline(always)]
+  pub const fn as_mut_ptr(&mut self) -> *mut T {
+    self.ptr.as_ptr()
+  }
+
   #[inline(always)]
   pub fn len(&self) -> usize {
     unsafe {
@@ -124,6 +169,8 @@ impl<T: FFISafe + Sized> Vector<T> {
 
   #[inline(always)]
   pub fn allocate(&mut self, known_cap: Option<usize>, capacity: NonZeroUsize) {
+    self.check_known(None, known_cap);
+
     let capacity = capacity.get();
 
     let cap = known_cap.unwrap_or(self.cap());
@@ -169,6 +216,8 @@ impl<T: FFISafe + Sized> Vector<T> {
     known_cap: Option<usize>,
     value: T,
   ) {
+    self.check_known(known_len, known_cap);
+
     let len = known_len.unwrap_or(self.len());
 
     // Capacity for a push is always at least 1, so this is safe.
@@ -203,6 +252,8 @@ impl<T: FFISafe + Sized> Vector<T> {
   ) where
     I: IntoIterator<Item = T>,
   {
+    self.check_known(known_len, known_cap);
+
     let mut iterator = iter.into_iter();
     let (lower, _) = iterator.size_hint();
 
@@ -217,15 +268,20 @@ impl<T: FFISafe + Sized> Vector<T> {
 
     // We still have to loop, but if the iterator is 'TrustedLen',
     // the compiler can optimize this loop into a single block move.
+    // The capacity changes whenever a push reallocates, so only the length is passed on
     while let Some(item) = iterator.next() {
       unsafe {
-        self.push_aided(Some(len), known_cap, item);
+        self.push_aided(Some(len), None, item);
       }
 
       len += 1;
     }
   }
 
+  /// Gives up ownership, returning the pointer to the first element
+  ///
+  /// The vector must be given back to [`Vector::from_raw`], possibly in another dylib,
+  /// to be freed.
   pub fn into_raw(self) -> *mut T {
     let data = self.ptr.as_ptr();
     forget(self);
@@ -233,10 +289,58 @@ impl<T: FFISafe + Sized> Vector<T> {
     data
   }
 
-  pub unsafe fn from_raw(ptr: NonNull<T>) -> Self {
+  #[track_caller]
+  /// Takes ownership of a pointer returned by [`Vector::into_raw`], `None` if it is null
+  ///
+  /// # Safety
+  ///
+  /// The pointer must come from `into_raw` of a `Vector<T>` built with the same saffi
+  /// version, and nobody else may own it.
+  pub unsafe fn from_raw(ptr: *mut T) -> Option<Self> {
+    Some(unsafe { Self::from_nonnull(NonNull::new(ptr)?) })
+  }
+
+  #[track_caller]
+  /// Same as [`Vector::from_raw`] for a pointer that is known not to be null
+  ///
+  /// # Safety
+  ///
+  /// See [`Vector::from_raw`]
+  pub unsafe fn from_nonnull(ptr: NonNull<T>) -> Self {
+    unsafe {
+      hardened::verify(
+        ptr.as_ptr().byte_offset(header_offset::<T>()) as _,
+        TAG_VECTOR,
+      )
+    };
+
     Self { ptr }
   }
 
+  /// Gives up ownership without ever freeing the vector, returning its elements
+  pub fn leak<'a>(self) -> &'a mut [T] {
+    let len = self.len();
+
+    unsafe { core::slice::from_raw_parts_mut(self.into_raw(), len) }
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`Vector::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the vector is dropped or reallocates.
+  pub const fn as_raw(&self) -> *const T {
+    self.ptr.as_ptr()
+  }
+
+  /// Lends the pointer of [`Vector::into_raw`] to `f`, e.g. to hand it to foreign code
+  /// for the duration of a call
+  ///
+  /// `f` may read and write the elements through it, but must neither free it, nor
+  /// reallocate it, nor keep it after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut T) -> R) -> R {
+    f(self.ptr.as_ptr())
+  }
+
   pub fn extend_array<const N: usize>(&mut self, value: [T; N]) {
     unsafe { self.extend_array_aided(None, None, value) };
   }
@@ -247,6 +351,8 @@ impl<T: FFISafe + Sized> Vector<T> {
     known_cap: Option<usize>,
     value: [T; N],
   ) {
+    self.check_known(known_len, known_cap);
+
     if N == 0 {
       return;
     }
@@ -283,6 +389,8 @@ impl<T: FFISafe + Sized> Vector<T> {
   ) where
     T: Copy,
   {
+    self.check_known(known_len, known_cap);
+
     let len = known_len.unwrap_or(self.len());
     let new_len = len + value.len();
 
@@ -303,6 +411,8 @@ impl<T: FFISafe + Sized> Vector<T> {
 
   #[inline(always)]
   pub unsafe fn get_aided(&self, known_len: Option<usize>, index: usize) -> Option<&T> {
+    self.check_known(known_len, None);
+
     let len = known_len.unwrap_or(self.len());
 
     if index >= len {
@@ -314,6 +424,8 @@ impl<T: FFISafe + Sized> Vector<T> {
 
   #[inline(always)]
   pub unsafe fn get_mut_aided(&mut self, known_len: Option<usize>, index: usize) -> Option<&mut T> {
+    self.check_known(known_len, None);
+
     let len = known_len.unwrap_or(self.len());
 
     if index >= len {
@@ -330,6 +442,8 @@ impl<T: FFISafe + Sized> Vector<T> {
 
   #[inline(always)]
   pub unsafe fn pop_aided(&mut self, known_len: Option<usize>) -> Option<T> {
+    self.check_known(known_len, None);
+
     let len = known_len.unwrap_or(self.len());
 
     if len == 0 {
@@ -387,18 +501,24 @@ impl<T: FFISafe + Sized> Deref for Vector<T> {
   type Target = [T];
 
   fn deref(&self) -> &Self::Target {
+    self.verify();
+
     unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len()) }
   }
 }
 
 impl<T: FFISafe + Sized> DerefMut for Vector<T> {
   fn deref_mut(&mut self) -> &mut Self::Target {
+    self.verify();
+
     unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len()) }
   }
 }
 
 impl<T: FFISafe + Sized> Drop for Vector<T> {
   fn drop(&mut self) {
+    self.verify();
+
     unsafe {
       if needs_drop::<T>() {
         for i in (0..self.len()).rev() {
@@ -408,7 +528,11 @@ impl<T: FFISafe + Sized> Drop for Vector<T> {
         }
       }
 
-      salloc::aligned_free(self.ptr.as_ptr().byte_offset(header_offset::<T>()) as _)
+      hardened::poison(
+        self.header(),
+        data_offset::<T>() as usize + self.cap() * size_of::<T>(),
+      );
+      salloc::aligned_free(self.header())
     };
   }
 }
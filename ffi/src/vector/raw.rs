@@ -0,0 +1,452 @@
+use core::ffi::c_void;
+use core::{alloc::Layout, ptr};
+use std::hint::cold_path;
+use std::mem::{forget, needs_drop, offset_of};
+use std::ptr::NonNull;
+
+use crate::{
+  FFISafe,
+  hardened::{self, TAG_VECTOR},
+  vector::{Vector, VectorHeaderVTable},
+};
+
+/// The drop function stored by a [`RawVector`]
+///
+/// It receives a pointer to a single element and must drop it in place
+/// without freeing the memory backing it.
+pub type RawDropFn = unsafe extern "C" fn(elem: *mut c_void);
+
+/// The non-generic part of [`VectorHeaderVTable`]
+///
+/// A [`RawVector`] shares the exact same header with a `Vector<T>`, only the
+/// offset of the data is calculated at runtime.
+#[repr(C)]
+struct RawVectorHeader {
+  #[cfg(feature = "hardened")]
+  _guard: hardened::Guard,
+
+  len: usize,
+  cap: usize,
+}
+
+const _SAFETY_LEN: () =
+  assert!(offset_of!(RawVectorHeader, len) == offset_of!(VectorHeaderVTable<u8>, len));
+const _SAFETY_CAP: () =
+  assert!(offset_of!(RawVectorHeader, cap) == offset_of!(VectorHeaderVTable<u8>, cap));
+
+#[repr(C)]
+/// A type-erased [`Vector`] whose element layout is only known at runtime
+///
+/// This is meant for the Sa VM, which decides the size and the alignment of the
+/// elements of its arrays while running. The allocation is laid out exactly like a
+/// `Vector<T>` with the same element layout, so the two can be converted into each
+/// other without reallocating.
+pub struct RawVector {
+  ptr: NonNull<u8>,
+
+  size: usize,
+  align: usize,
+
+  drop: Option<RawDropFn>,
+}
+
+// Internal machinery to drop a single element of a known type.
+unsafe extern "C" fn drop_elem<T: FFISafe>(elem: *mut c_void) {
+  unsafe { ptr::drop_in_place(elem as *mut T) }
+}
+
+impl RawVector {
+  /// Creates a new, empty vector for elements of the given layout
+  ///
+  /// # Safety
+  ///
+  /// If `drop` is provided, it must be safe to call on every element that is ever
+  /// stored in this vector through [`RawVector::push`] or [`RawVector::set`].
+  pub unsafe fn new(layout: Layout, drop: Option<RawDropFn>) -> Self {
+    const DEF_CAP: usize = 2;
+
+    let mut out = Self {
+      ptr: NonNull::dangling(),
+      size: layout.size(),
+      align: layout.align(),
+      drop,
+    };
+
+    let ptr = unsafe { salloc::aligned_malloc(out.alloc_size(DEF_CAP), out.alloc_align()) };
+
+    if ptr.is_null() {
+      panic!("Allocation Failed");
+    }
+
+    unsafe {
+      ptr::write(
+        ptr as *mut RawVectorHeader,
+        RawVectorHeader {
+          #[cfg(feature = "hardened")]
+          _guard: hardened::Guard::new(TAG_VECTOR),
+          len: 0,
+          cap: DEF_CAP,
+        },
+      );
+
+      out.ptr = NonNull::new_unchecked((ptr as *mut u8).add(out.data_offset()));
+    }
+
+    out
+  }
+
+  /// Creates a new, empty vector whose layout and drop function are taken from `T`
+  pub fn of<T: FFISafe>() -> Self {
+    // SAFETY: The drop function is the one of `T`
+    unsafe { Self::new(Layout::new::<T>(), drop_fn::<T>()) }
+  }
+
+  #[inline(always)]
+  /// The layout of a single element
+  pub fn elem_layout(&self) -> Layout {
+    // SAFETY: These values were taken from a valid Layout
+    unsafe { Layout::from_size_align_unchecked(self.size, self.align) }
+  }
+
+  #[inline(always)]
+  pub fn drop_fn(&self) -> Option<RawDropFn> {
+    self.drop
+  }
+
+  /// Same as [`data_offset`](crate::vector::data_offset) but for the runtime layout
+  #[inline(always)]
+  fn data_offset(&self) -> usize {
+    size_of::<RawVectorHeader>().next_multiple_of(self.align)
+  }
+
+  #[inline(always)]
+  fn alloc_align(&self) -> usize {
+    self
+      .align
+      .max(align_of::<RawVectorHeader>())
+      .max(size_of::<*const c_void>())
+  }
+
+  #[inline(always)]
+  fn alloc_size(&self, count: usize) -> usize {
+    let Some(size) = count
+      .checked_mul(self.size)
+      .and_then(|x| x.checked_add(self.data_offset()))
+    else {
+      cold_path();
+      panic!("capacity overflow");
+    };
+
+    size.next_multiple_of(self.alloc_align())
+  }
+
+  #[inline(always)]
+  fn header(&self) -> *mut RawVectorHeader {
+    unsafe { self.ptr.as_ptr().sub(self.data_offset()) as _ }
+  }
+
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    unsafe { (*self.header()).len }
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  #[inline(always)]
+  pub fn cap(&self) -> usize {
+    unsafe { (*self.header()).cap }
+  }
+
+  #[inline(always)]
+  fn set_len(&mut self, len: usize) {
+    unsafe { (*self.header()).len = len };
+  }
+
+  #[inline(always)]
+  fn elem_ptr(&self, index: usize) -> *mut u8 {
+    unsafe { self.ptr.as_ptr().add(index * self.size) }
+  }
+
+  /// Makes sure that the vector can hold at least `capacity` elements
+  pub fn allocate(&mut self, capacity: usize) {
+    let cap = self.cap();
+
+    if cap < capacity {
+      let new_cap = (cap * 2).max(capacity);
+
+      let new_block = unsafe {
+        salloc::aligned_realloc(
+          self.header() as _,
+          self.alloc_size(new_cap),
+          self.alloc_align(),
+        )
+      };
+
+      if new_block.is_null() {
+        panic!("Allocation Failed");
+      }
+
+      unsafe {
+        self.ptr = NonNull::new_unchecked((new_block as *mut u8).add(self.data_offset()));
+
+        (*self.header()).cap = new_cap;
+      }
+    }
+  }
+
+  #[inline(always)]
+  fn check_elem(&self, value: &[u8]) {
+    if value.len() != self.size {
+      cold_path();
+
+      panic!(
+        "element size mismatch: the element size is {} but the value is {} bytes",
+        self.size,
+        value.len()
+      );
+    }
+  }
+
+  /// Pushes a copy of the given bytes as a new element
+  ///
+  /// The vector takes ownership of the element, so it will be dropped
+  /// with the drop function of this vector.
+  ///
+  /// # Safety
+  ///
+  /// The bytes must be a valid value of the element type of this vector, the drop
+  /// function will be called on them.
+  ///
+  /// # Panics
+  ///
+  /// If `value` is not exactly as long as the element size
+  pub unsafe fn push(&mut self, value: &[u8]) {
+    self.check_elem(value);
+
+    let len = self.len();
+    self.allocate(len + 1);
+
+    unsafe {
+      ptr::copy_nonoverlapping(value.as_ptr(), self.elem_ptr(len), self.size);
+    }
+
+    self.set_len(len + 1);
+  }
+
+  /// Moves the last element out into `out`
+  ///
+  /// The element is NOT dropped, its ownership is transferred to the
+  /// bytes written into `out`. Returns `false` if the vector was empty.
+  ///
+  /// # Panics
+  ///
+  /// If `out` is not exactly as long as the element size
+  pub fn pop(&mut self, out: &mut [u8]) -> bool {
+    self.check_elem(out);
+
+    let len = self.len();
+
+    if len == 0 {
+      cold_path();
+      return false;
+    }
+
+    self.set_len(len - 1);
+
+    unsafe {
+      ptr::copy_nonoverlapping(self.elem_ptr(len - 1), out.as_mut_ptr(), self.size);
+    }
+
+    true
+  }
+
+  #[inline(always)]
+  pub fn get(&self, index: usize) -> Option<&[u8]> {
+    if index >= self.len() {
+      return None;
+    }
+
+    Some(unsafe { core::slice::from_raw_parts(self.elem_ptr(index), self.size) })
+  }
+
+  #[inline(always)]
+  /// Returns a pointer to the element, which is aligned to the element layout
+  pub fn get_ptr(&self, index: usize) -> Option<NonNull<u8>> {
+    if index >= self.len() {
+      return None;
+    }
+
+    Some(unsafe { NonNull::new_unchecked(self.elem_ptr(index)) })
+  }
+
+  /// Replaces the element at `index` with a copy of the given bytes
+  ///
+  /// The previous element is dropped. Returns `false` if `index` is out of bounds.
+  ///
+  /// # Safety
+  ///
+  /// Same as [`RawVector::push`], the bytes must be a valid value of the element type
+  ///
+  /// # Panics
+  ///
+  /// If `value` is not exactly as long as the element size
+  pub unsafe fn set(&mut self, index: usize, value: &[u8]) -> bool {
+    self.check_elem(value);
+
+    if index >= self.len() {
+      cold_path();
+      return false;
+    }
+
+    let dst = self.elem_ptr(index);
+
+    unsafe {
+      if let Some(drop) = self.drop {
+        drop(dst as _);
+      }
+
+      ptr::copy_nonoverlapping(value.as_ptr(), dst, self.size);
+    }
+
+    true
+  }
+
+  /// Gives up ownership, returning the pointer to the first element
+  ///
+  /// The vector must be given back to [`RawVector::from_raw`], possibly in another
+  /// dylib, to be freed.
+  pub fn into_raw(self) -> *mut u8 {
+    let data = self.ptr.as_ptr();
+    forget(self);
+
+    data
+  }
+
+  #[track_caller]
+  /// Takes ownership of a pointer returned by [`RawVector::into_raw`], `None` if it is
+  /// null
+  ///
+  /// # Safety
+  ///
+  /// The pointer must come from `into_raw` of a `RawVector` built with the same saffi
+  /// version, `layout` & `drop` must be its element layout & drop function, and nobody
+  /// else may own it.
+  pub unsafe fn from_raw(ptr: *mut u8, layout: Layout, drop: Option<RawDropFn>) -> Option<Self> {
+    Some(unsafe { Self::from_nonnull(NonNull::new(ptr)?, layout, drop) })
+  }
+
+  #[track_caller]
+  /// Same as [`RawVector::from_raw`] for a pointer that is known not to be null
+  ///
+  /// # Safety
+  ///
+  /// See [`RawVector::from_raw`]
+  pub unsafe fn from_nonnull(ptr: NonNull<u8>, layout: Layout, drop: Option<RawDropFn>) -> Self {
+    let out = Self {
+      ptr,
+      size: layout.size(),
+      align: layout.align(),
+      drop,
+    };
+
+    unsafe { hardened::verify(out.header() as _, TAG_VECTOR) };
+
+    out
+  }
+
+  /// Gives up ownership without ever freeing the vector, returning its elements
+  ///
+  /// Unlike [`Vector::leak`] this is a pointer, writing arbitrary bytes through it is
+  /// only sound if they are valid values of the element type.
+  pub fn leak(self) -> NonNull<[u8]> {
+    let len = self.len() * self.size;
+
+    NonNull::slice_from_raw_parts(unsafe { NonNull::new_unchecked(self.into_raw()) }, len)
+  }
+
+  #[inline(always)]
+  /// Returns the same pointer as [`RawVector::into_raw`] without giving up ownership
+  ///
+  /// It stays valid until the vector is dropped or reallocates.
+  pub const fn as_raw(&self) -> *const u8 {
+    self.ptr.as_ptr()
+  }
+
+  /// Lends the pointer of [`RawVector::into_raw`] to `f`, e.g. to hand it to foreign
+  /// code for the duration of a call
+  ///
+  /// `f` may read and write the elements through it, but must neither free it, nor
+  /// reallocate it, nor keep it after returning.
+  pub fn with_raw<R>(&mut self, f: impl FnOnce(*mut u8) -> R) -> R {
+    f(self.ptr.as_ptr())
+  }
+
+  /// Converts this vector into a `Vector<T>`
+  ///
+  /// This never reallocates. It fails and gives the vector back when the layout of
+  /// `T` is not the element layout, or when the drop function does not agree with
+  /// the one of `T`.
+  ///
+  /// # Safety
+  ///
+  /// Every element of this vector must be a valid `T`. Matching layouts & drop
+  /// functions do not prove it, a `u32` is not always a valid `char`.
+  pub unsafe fn try_into_vector<T: FFISafe>(self) -> Result<Vector<T>, Self> {
+    let layout = Layout::new::<T>();
+
+    if layout.size() != self.size || layout.align() != self.align {
+      return Err(self);
+    }
+
+    match (self.drop, drop_fn::<T>()) {
+      (None, None) => {}
+      (Some(a), Some(b)) if ptr::fn_addr_eq(a, b) => {}
+      _ => return Err(self),
+    }
+
+    let ptr = self.ptr.cast::<T>();
+    forget(self);
+
+    // SAFETY: The allocation is laid out exactly like a `Vector<T>`
+    Ok(unsafe { Vector::from_nonnull(ptr) })
+  }
+}
+
+fn drop_fn<T: FFISafe>() -> Option<RawDropFn> {
+  if needs_drop::<T>() {
+    Some(drop_elem::<T>)
+  } else {
+    None
+  }
+}
+
+impl<T: FFISafe> From<Vector<T>> for RawVector {
+  fn from(value: Vector<T>) -> Self {
+    Self {
+      ptr: unsafe { NonNull::new_unchecked(value.into_raw() as *mut u8) },
+      size: size_of::<T>(),
+      align: align_of::<T>(),
+      drop: drop_fn::<T>(),
+    }
+  }
+}
+
+impl Drop for RawVector {
+  fn drop(&mut self) {
+    unsafe {
+      hardened::verify(self.header() as _, TAG_VECTOR);
+
+      if let Some(drop) = self.drop {
+        for i in (0..self.len()).rev() {
+          drop(self.elem_ptr(i) as _);
+        }
+      }
+
+      hardened::poison(self.header() as _, self.alloc_size(self.cap()));
+      salloc::aligned_free(self.header() as _);
+    }
+  }
+}
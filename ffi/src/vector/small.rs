@@ -0,0 +1,259 @@
+use core::{
+  mem::{ManuallyDrop, MaybeUninit},
+  ops::{Deref, DerefMut},
+  ptr,
+};
+use std::hint::cold_path;
+use std::mem::{forget, needs_drop};
+
+use crate::{
+  FFISafe,
+  I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT,
+  vector::Vector,
+};
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmallVecTag {
+  /// The elements are stored in `SmallVecData::inline`, `len` is the length
+  Inline = 0,
+  /// The elements are stored in `SmallVecData::heap`, `len` is unused
+  Heap = 1,
+}
+
+#[repr(C)]
+pub union SmallVecData<T: FFISafe + Sized, const N: usize> {
+  pub inline: ManuallyDrop<[MaybeUninit<T>; N]>,
+  /// This is exactly a `Vector<T>`, i.e. a pointer to the data of a vector
+  /// allocated through salloc with its `len` & `cap` header in front of it
+  pub heap: ManuallyDrop<Vector<T>>,
+}
+
+#[repr(C)]
+/// A vector that stores up to `N` elements inline before spilling into a [`Vector`]
+///
+/// # Layout
+///
+/// The layout is guaranteed to be the following C struct, so foreign code can
+/// read it without knowing anything else about it
+///
+/// ```c
+/// struct FfiSmallVec_T_N {
+///   uint8_t tag;  // 0 = Inline, 1 = Heap
+///   size_t len;   // Number of inline elements, unused when tag is Heap
+///   union {
+///     T inline[N];
+///     T *heap;    // Same as Vector<T>, see below
+///   } data;
+/// };
+/// ```
+///
+/// `heap` points to the first element, the `len` & `cap` of the vector are the
+/// `((size_t *)heap)[-2]` & `((size_t *)heap)[-1]` as long as `alignof(T) <= 16`.
+/// Otherwise there is padding in between, the header then starts
+/// [`vector::data_offset::<T>()`](super::data_offset) bytes before `heap`, with `len`
+/// & `cap` right after the 16 byte guard of the `hardened` feature.
+pub struct FfiSmallVec<T: FFISafe + Sized, const N: usize> {
+  tag: SmallVecTag,
+  len: usize,
+  data: SmallVecData<T, N>,
+}
+
+unsafe impl<T: FFISafe + Sized, const N: usize> FFISafe for FfiSmallVec<T, N> {
+  fn i_am_ffisafe() -> crate::IAmFFISafe {
+    I_DECLARE_THAT_I_AND_MY_CODEBASE_IS_FFI_SAFE_AND_THAT_UNDEFINED_BEHAVIOUR_ARISING_DUE_TO_DECLARING_MY_TYPES_FFI_SAFE_DOES_NOT_CONDONE_THE_SAFETY_AND_SECURITY_OF_THIS_PROJECT
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> Default for FfiSmallVec<T, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> FfiSmallVec<T, N> {
+  /// Creates an empty, inline vector. This does not allocate.
+  pub const fn new() -> Self {
+    Self {
+      tag: SmallVecTag::Inline,
+      len: 0,
+      data: SmallVecData {
+        inline: ManuallyDrop::new([const { MaybeUninit::uninit() }; N]),
+      },
+    }
+  }
+
+  #[inline(always)]
+  pub const fn tag(&self) -> SmallVecTag {
+    self.tag
+  }
+
+  #[inline(always)]
+  pub const fn spilled(&self) -> bool {
+    matches!(self.tag, SmallVecTag::Heap)
+  }
+
+  #[inline(always)]
+  pub fn len(&self) -> usize {
+    match self.tag {
+      SmallVecTag::Inline => self.len,
+      SmallVecTag::Heap => unsafe { self.data.heap.len() },
+    }
+  }
+
+  #[inline(always)]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  #[inline(always)]
+  pub fn cap(&self) -> usize {
+    match self.tag {
+      SmallVecTag::Inline => N,
+      SmallVecTag::Heap => unsafe { self.data.heap.cap() },
+    }
+  }
+
+  #[inline(always)]
+  fn as_ptr(&self) -> *const T {
+    match self.tag {
+      SmallVecTag::Inline => unsafe { self.data.inline.as_ptr() as *const T },
+      SmallVecTag::Heap => unsafe { self.data.heap.as_ptr() },
+    }
+  }
+
+  #[inline(always)]
+  fn as_mut_ptr(&mut self) -> *mut T {
+    match self.tag {
+      SmallVecTag::Inline => unsafe { (*self.data.inline).as_mut_ptr() as *mut T },
+      SmallVecTag::Heap => unsafe { (*self.data.heap).as_mut_ptr() },
+    }
+  }
+
+  /// Moves the inline elements into a freshly allocated [`Vector`]
+  #[cold]
+  fn spill(&mut self, capacity: usize) {
+    debug_assert!(!self.spilled());
+
+    let mut heap = Vector::<T>::new();
+
+    unsafe {
+      heap.allocate(
+        None,
+        core::num::NonZeroUsize::new_unchecked(capacity.max(N + 1)),
+      );
+
+      // Move the bits, the inline copies are forgotten by setting len to 0
+      let src = self.data.inline.as_ptr() as *const T;
+      ptr::copy_nonoverlapping(src, heap.as_mut_ptr(), self.len);
+      heap.set_len(self.len);
+
+      self.len = 0;
+      self.tag = SmallVecTag::Heap;
+      self.data = SmallVecData {
+        heap: ManuallyDrop::new(heap),
+      };
+    }
+  }
+
+  #[inline(always)]
+  pub fn push(&mut self, value: T) {
+    if !self.spilled() {
+      if self.len < N {
+        unsafe {
+          (*self.data.inline)[self.len].write(value);
+        }
+
+        self.len += 1;
+        return;
+      }
+
+      self.spill(N * 2);
+    }
+
+    unsafe { (*self.data.heap).push(value) }
+  }
+
+  #[inline(always)]
+  pub fn pop(&mut self) -> Option<T> {
+    if self.spilled() {
+      return unsafe { (*self.data.heap).pop() };
+    }
+
+    if self.len == 0 {
+      cold_path();
+      return None;
+    }
+
+    self.len -= 1;
+
+    Some(unsafe { (*self.data.inline)[self.len].assume_init_read() })
+  }
+
+  pub fn extend<I>(&mut self, iter: I)
+  where
+    I: IntoIterator<Item = T>,
+  {
+    for item in iter {
+      self.push(item);
+    }
+  }
+
+  /// Converts this into a [`Vector`], allocating only if it was not spilled yet
+  pub fn into_vector(mut self) -> Vector<T> {
+    if !self.spilled() {
+      self.spill(self.len.max(1));
+    }
+
+    let out = unsafe { ManuallyDrop::take(&mut self.data.heap) };
+    forget(self);
+
+    out
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> From<Vector<T>> for FfiSmallVec<T, N> {
+  /// This keeps the elements in the given allocation, even if they would fit inline
+  fn from(value: Vector<T>) -> Self {
+    Self {
+      tag: SmallVecTag::Heap,
+      len: 0,
+      data: SmallVecData {
+        heap: ManuallyDrop::new(value),
+      },
+    }
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> Deref for FfiSmallVec<T, N> {
+  type Target = [T];
+
+  fn deref(&self) -> &Self::Target {
+    unsafe { core::slice::from_raw_parts(self.as_ptr(), self.len()) }
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> DerefMut for FfiSmallVec<T, N> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    unsafe { core::slice::from_raw_parts_mut(self.as_mut_ptr(), self.len()) }
+  }
+}
+
+impl<T: FFISafe + Sized, const N: usize> Drop for FfiSmallVec<T, N> {
+  fn drop(&mut self) {
+    unsafe {
+      match self.tag {
+        SmallVecTag::Heap => ManuallyDrop::drop(&mut self.data.heap),
+        SmallVecTag::Inline => {
+          if needs_drop::<T>() {
+            let ptr = self.as_mut_ptr();
+
+            for i in (0..self.len).rev() {
+              ptr::drop_in_place(ptr.add(i));
+            }
+          }
+        }
+      }
+    }
+  }
+}
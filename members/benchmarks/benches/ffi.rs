@@ -45,3 +45,15 @@ fn flood(id: Instruction) {
     while let Some(_) = tasks.next().await {}
   });
 }
+
+/// Creates & awaits futures one after the other, so every state can reuse the last one
+///
+/// Compare against `cargo bench --bench ffi --features pool`
+#[divan::bench(sample_size = 1)]
+fn churn() {
+  RT_MUL.block_on(async {
+    for _ in 0..50_000 {
+      FFIFuture::new(benchmarks::asyncfn::none()).await.unwrap();
+    }
+  });
+}
@@ -0,0 +1,28 @@
+use saffi::vector::{Vector, small::FfiSmallVec};
+
+fn main() {
+  // Run registered benchmarks.
+  divan::main();
+}
+
+#[divan::bench(args = [1, 4, 8, 16, 64])]
+fn vector(n: u64) -> Vector<u64> {
+  let mut vec = Vector::new();
+
+  for i in 0..divan::black_box(n) {
+    vec.push(i);
+  }
+
+  vec
+}
+
+#[divan::bench(args = [1, 4, 8, 16, 64])]
+fn smallvec(n: u64) -> FfiSmallVec<u64, 8> {
+  let mut vec = FfiSmallVec::new();
+
+  for i in 0..divan::black_box(n) {
+    vec.push(i);
+  }
+
+  vec
+}
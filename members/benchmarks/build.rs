@@ -16,9 +16,15 @@ fn main() {
   // SaAlloc is already built by asyncs!
 
   // Build asyncs
-  if !Command::new("cargo")
-    .arg("build")
-    .arg("--release")
+  let mut build = Command::new("cargo");
+  build.arg("build").arg("--release");
+
+  // The futures are allocated by asyncs, so it has to be built with the pool as well
+  if std::env::var_os("CARGO_FEATURE_POOL").is_some() {
+    build.arg("--features").arg("pool");
+  }
+
+  if !build
     .current_dir(&asyncs)
     .env("CARGO_TARGET_DIR", common_targets)
     .spawn()
@@ -0,0 +1,171 @@
+use std::{hint::cold_path, ptr};
+
+use loom::{
+  cell::UnsafeCell,
+  sync::atomic::{AtomicPtr, AtomicU8, AtomicUsize, Ordering},
+};
+
+// Tiny segments so that the models race on segment allocation too
+const FIRST_SHIFT: u32 = 0;
+const SEGMENTS: usize = 4;
+
+const EMPTY: u8 = 0;
+const WRITTEN: u8 = 1;
+const WAITED: u8 = 2;
+
+struct Slot<T> {
+  state: AtomicU8,
+  value: UnsafeCell<Option<T>>,
+}
+
+const fn seg_len(seg: usize) -> usize {
+  1 << (seg as u32 + FIRST_SHIFT)
+}
+
+const fn locate(index: usize) -> (usize, usize) {
+  let n = index + (1 << FIRST_SHIFT);
+  let bit = usize::BITS - 1 - n.leading_zeros();
+
+  let seg = (bit - FIRST_SHIFT) as usize;
+
+  (seg, n - (1 << bit))
+}
+
+pub struct FfiConcurrentVec<T> {
+  reserved: AtomicUsize,
+  len: AtomicUsize,
+
+  segments: [AtomicPtr<Slot<T>>; SEGMENTS],
+}
+
+unsafe impl<T: Send> Send for FfiConcurrentVec<T> {}
+unsafe impl<T: Send + Sync> Sync for FfiConcurrentVec<T> {}
+
+impl<T> FfiConcurrentVec<T> {
+  pub fn new() -> Self {
+    Self {
+      reserved: AtomicUsize::new(0),
+      len: AtomicUsize::new(0),
+      segments: std::array::from_fn(|_| AtomicPtr::new(ptr::null_mut())),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.len.load(Ordering::Acquire)
+  }
+
+  fn alloc_segment(&self, seg: usize) -> *mut Slot<T> {
+    let new = Box::into_raw(
+      (0..seg_len(seg))
+        .map(|_| Slot::<T> {
+          state: AtomicU8::new(EMPTY),
+          value: UnsafeCell::new(None),
+        })
+        .collect::<Box<[_]>>(),
+    ) as *mut Slot<T>;
+
+    match self.segments[seg].compare_exchange(
+      ptr::null_mut(),
+      new,
+      Ordering::AcqRel,
+      Ordering::Acquire,
+    ) {
+      Ok(_) => new,
+      Err(other) => {
+        unsafe {
+          drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            new,
+            seg_len(seg),
+          )))
+        };
+
+        other
+      }
+    }
+  }
+
+  fn slot(&self, index: usize) -> *mut Slot<T> {
+    let (seg, offset) = locate(index);
+
+    let mut base = self.segments[seg].load(Ordering::Acquire);
+
+    if base.is_null() {
+      cold_path();
+      base = self.alloc_segment(seg);
+    }
+
+    unsafe { base.add(offset) }
+  }
+
+  pub fn push(&self, value: T) -> usize {
+    let index = self.reserved.fetch_add(1, Ordering::Relaxed);
+
+    let prev = unsafe {
+      let slot = &*self.slot(index);
+
+      slot.value.with_mut(|x| *x = Some(value));
+      slot.state.swap(WRITTEN, Ordering::AcqRel)
+    };
+
+    if prev == WAITED || index == 0 {
+      self.publish(index);
+    }
+
+    index
+  }
+
+  fn publish(&self, start: usize) {
+    let mut len = start;
+
+    loop {
+      let slot = unsafe { &*self.slot(len) };
+
+      match slot
+        .state
+        .compare_exchange(EMPTY, WAITED, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => return,
+        Err(_) => {
+          len += 1;
+          self.len.store(len, Ordering::Release);
+        }
+      }
+    }
+  }
+
+  /// Returns a copy of the published prefix, like iterating a `Snapshot`
+  pub fn snapshot(&self) -> Vec<T>
+  where
+    T: Clone,
+  {
+    let len = self.len();
+
+    (0..len)
+      .map(|i| {
+        let (seg, offset) = locate(i);
+        let base = self.segments[seg].load(Ordering::Acquire);
+
+        unsafe { &*base.add(offset) }
+          .value
+          .with(|x| unsafe { (*x).clone() }.expect("Published an unwritten slot"))
+      })
+      .collect()
+  }
+}
+
+impl<T> Drop for FfiConcurrentVec<T> {
+  fn drop(&mut self) {
+    for (seg, base) in self.segments.iter_mut().enumerate() {
+      let base = base.load(Ordering::Relaxed);
+
+      if !base.is_null() {
+        unsafe {
+          drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            base,
+            seg_len(seg),
+          )))
+        };
+      }
+    }
+  }
+}
@@ -0,0 +1,61 @@
+use loom::{sync::Arc, thread};
+
+use crate::concvec::im::FfiConcurrentVec;
+
+mod im;
+
+#[test]
+pub fn loom_concurrent_push() {
+  loom::model(|| {
+    let vec = Arc::new(FfiConcurrentVec::<usize>::new());
+
+    let v1 = vec.clone();
+    let jh1 = thread::spawn(move || {
+      v1.push(1);
+    });
+
+    let v2 = vec.clone();
+    let jh2 = thread::spawn(move || {
+      v2.push(2);
+    });
+
+    jh1.join().unwrap();
+    jh2.join().unwrap();
+
+    // The last writer to finish must have published everything
+    let mut all = vec.snapshot();
+    all.sort();
+
+    assert_eq!(all, [1, 2]);
+  });
+}
+
+#[test]
+pub fn loom_snapshot_while_pushing() {
+  loom::model(|| {
+    let vec = Arc::new(FfiConcurrentVec::<usize>::new());
+
+    let v1 = vec.clone();
+    let jh1 = thread::spawn(move || {
+      v1.push(1);
+      v1.push(3);
+    });
+
+    let v2 = vec.clone();
+    let jh2 = thread::spawn(move || {
+      v2.push(2);
+    });
+
+    // Snapshots only contain written elements, and never shrink
+    let first = vec.snapshot();
+    let second = vec.snapshot();
+
+    assert!(first.len() <= second.len());
+    assert_eq!(&second[..first.len()], &first[..]);
+
+    jh1.join().unwrap();
+    jh2.join().unwrap();
+
+    assert_eq!(vec.len(), 3);
+  });
+}
@@ -1,6 +1,7 @@
 #![allow(dead_code, unused_imports)]
 
 mod arcstore;
+mod concvec;
 mod waker;
 fn main() {
   println!("Hello, world!");